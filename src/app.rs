@@ -0,0 +1,116 @@
+//! Running the bar as a whole, as the `tid` binary does.
+
+use battery::Manager;
+use sysinfo::{System, SystemExt};
+use winit::event_loop::{EventLoop, EventLoopBuilder};
+
+use crate::config::{Config, Output};
+use crate::source::sysfs::{CpuFrequencies, Hwmon};
+use crate::source::{background, fake, FrequencySource, SensorSource, SourceKind, Sources};
+use crate::{font, output, render, schedule, terminal, window, State};
+
+/// Run the bar as set out in `config`, until it is closed or an error occurs.
+///
+/// Depending on the configuration, the bar is shown in a window, written to stdout, or rendered
+/// to a png file once.
+pub fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    let font_path = &config.font_path;
+    let font = font::load_font(font_path)
+        .map_err(|err| format!("Failed to load font from {font_path:?}: {err}"))?;
+
+    // The event loop is only needed to draw into a window, but it has to exist before the
+    // background sources are started, so that they can wake it up when something changes.
+    let event_loop = (config.output == Output::Window && config.render_png.is_none())
+        .then(|| EventLoopBuilder::with_user_event().build());
+    let proxy = event_loop.as_ref().map(EventLoop::create_proxy);
+    let notify = move |source: SourceKind| {
+        if let Some(proxy) = &proxy {
+            // If the event loop is gone, there is nobody left to notify anyway.
+            let _ = proxy.send_event(source);
+        }
+    };
+
+    let sources = match config.demo {
+        true => fake::demo(),
+        false => system_sources(&config, notify),
+    };
+    let mut state = State::new(
+        font,
+        sources,
+        config.foreground,
+        config.background,
+        config.elements,
+    );
+    if let Some(graph_color) = config.graph_color {
+        state.graph_color = graph_color;
+    }
+
+    if let Some(path) = config.render_png {
+        return render::render_png(&mut state, &path)
+            .map_err(|err| format!("Failed to render to {path:?}: {err}").into());
+    }
+
+    let result = match config.output {
+        Output::Window => {
+            let event_loop = event_loop.expect("the event loop exists for window output");
+            return window::run(state, event_loop, config.position, config.renderer);
+        }
+        Output::I3bar => output::run_i3bar(&mut state),
+        Output::Text => output::run_text(&mut state, false),
+        Output::TextInPlace => output::run_text(&mut state, true),
+        Output::Sixel | Output::Kitty => {
+            let encode = match config.output {
+                Output::Sixel => terminal::sixel,
+                _ => terminal::kitty,
+            };
+            let scale_factor = window::env_scale_factor().unwrap_or(1) as usize;
+            output::run_graphics(&mut state, encode, scale_factor)
+        }
+    };
+    result.map_err(|err| format!("Failed to write to stdout: {err}").into())
+}
+
+/// The sources of actual system information for the elements in `config`.
+///
+/// Whenever a background source has something new, `notify` is called with its kind.
+fn system_sources(
+    config: &Config,
+    notify: impl Fn(SourceKind) + Clone + Send + 'static,
+) -> Sources {
+    // Only poll the battery and mpd if there are elements that need them, and do so on a worker
+    // thread, since they can be slow to respond.
+    let battery_interval = schedule::source_interval(&config.elements, SourceKind::Battery);
+    let music_interval = schedule::source_interval(&config.elements, SourceKind::Music);
+    // Where the kernel shows sensors and frequencies in sysfs, we read them ourselves. Elsewhere,
+    // sysinfo knows where to look.
+    let hwmon = Hwmon::new(&config.sysfs_root);
+    let sensors: Box<dyn SensorSource> = match hwmon.exists() {
+        true => Box::new(hwmon),
+        false => Box::new(System::new()),
+    };
+    let cpufreq = CpuFrequencies::new(&config.sysfs_root);
+    let frequency: Box<dyn FrequencySource> = match cpufreq.exists() {
+        true => Box::new(cpufreq),
+        false => Box::new(System::new()),
+    };
+    let notify_about = |source| {
+        let notify = notify.clone();
+        move || notify(source)
+    };
+    Sources {
+        sys: Box::new(System::new()),
+        sensors,
+        frequency,
+        battery: battery_interval.map(|interval| {
+            let connect = || Manager::new().ok()?.batteries().ok()?.next()?.ok();
+            let notify = notify_about(SourceKind::Battery);
+            Box::new(background::battery(connect, interval, notify)) as _
+        }),
+        music: music_interval.map(|interval| {
+            let addr = config.mpd_addr;
+            let connect = move || mpd::Client::connect(addr).ok();
+            let notify = notify_about(SourceKind::Music);
+            Box::new(background::music(connect, interval, notify)) as _
+        }),
+    }
+}
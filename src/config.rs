@@ -4,16 +4,21 @@ use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use chrono::Locale;
 use lexopt::{Arg, Parser, ValueExt};
 use winit::dpi::LogicalPosition;
 
-use crate::state::Element;
+use crate::element::{split_specification, Registry};
+use crate::schedule::Scheduled;
 
 const CONFIG_FILE_PATH: &str = "/etc/tid/tid.config";
 
 const DEFAULT_FONT_DIR: &str = "/etc/tid/fonts";
 const DEFAULT_FONT: &str = "cream12.uf2";
 const DEFAULT_MPD_ADDR: &str = "127.0.0.1:6600";
+const DEFAULT_SYSFS_ROOT: &str = "/sys";
+const DEFAULT_ELEMENTS: &str = "padding(3) date space time space label(bat) battery space \
+    label(mem) mem space label(cpu) cpu space cpugraph(120) space playbackstate padding(3)";
 const DEFAULT_BACKGROUND: Pixel = [0x00; PIXEL_SIZE];
 const DEFAULT_FOREGROUND: Pixel = [0xff; PIXEL_SIZE];
 
@@ -21,56 +26,203 @@ pub type Pixel = [u8; PIXEL_SIZE];
 pub const PIXEL_SIZE: usize = 4;
 const COLOR_PREFIX: &str = "0x";
 
+/// The way the window contents are presented.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    /// Use the gpu, and fall back to software rendering if that does not work out.
+    #[default]
+    Auto,
+    Gpu,
+    Software,
+}
+
+impl FromStr for Renderer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "gpu" => Ok(Self::Gpu),
+            "software" => Ok(Self::Software),
+            weird => Err(format!(
+                "unknown renderer '{weird}', expected 'auto', 'gpu', or 'software'"
+            )),
+        }
+    }
+}
+
+/// Where the bar is sent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// Draw the bar in its own window.
+    #[default]
+    Window,
+    /// Write the elements as blocks in the i3bar JSON protocol to stdout.
+    I3bar,
+    /// Write the elements as a line of plain text to stdout.
+    Text,
+    /// Write the elements as a line of plain text to stdout, overwriting the previous line.
+    TextInPlace,
+    /// Draw the bar in the terminal as sixel graphics.
+    Sixel,
+    /// Draw the bar in the terminal through the kitty graphics protocol.
+    Kitty,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "window" => Ok(Self::Window),
+            "i3bar" => Ok(Self::I3bar),
+            "text" => Ok(Self::Text),
+            "text-inplace" => Ok(Self::TextInPlace),
+            "sixel" => Ok(Self::Sixel),
+            "kitty" => Ok(Self::Kitty),
+            weird => Err(format!(
+                "unknown output '{weird}', expected 'window', 'i3bar', 'text', 'text-inplace', \
+                'sixel', or 'kitty'"
+            )),
+        }
+    }
+}
+
 pub struct Config {
-    pub elements: Vec<Element>,
+    pub elements: Vec<Scheduled>,
     pub font_path: Box<Path>,
     pub foreground: Pixel,
     pub background: Pixel,
+    /// The color of graphs, if it differs from the foreground color.
+    pub graph_color: Option<Pixel>,
     pub position: LogicalPosition<u32>,
     pub mpd_addr: SocketAddr,
+    pub output: Output,
+    pub renderer: Renderer,
+    pub locale: Locale,
+    pub sysfs_root: Box<Path>,
+    pub render_png: Option<Box<Path>>,
+    pub demo: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
+        let registry = Registry::default();
+        let elements = split_specification(DEFAULT_ELEMENTS)
+            .into_iter()
+            .map(|spec| {
+                registry
+                    .parse(spec)
+                    .expect("the default elements are valid")
+            })
+            .collect();
         Self {
-            elements: vec![
-                Element::Padding(3),
-                Element::Date(Default::default()),
-                Element::Space,
-                Element::Time(Default::default()),
-                Element::Space,
-                Element::Label("bat".to_string()),
-                Element::Battery(Default::default()),
-                Element::Space,
-                Element::Label("mem".to_string()),
-                Element::Mem(Default::default()),
-                Element::Space,
-                Element::Label("cpu".to_string()),
-                Element::Cpu(Default::default()),
-                Element::Space,
-                Element::CpuGraph(Default::default()),
-                Element::Space,
-                Element::PlaybackState(Default::default()),
-                Element::Padding(3),
-            ],
+            elements,
             font_path: PathBuf::from_iter([DEFAULT_FONT_DIR, DEFAULT_FONT]).into_boxed_path(),
             foreground: DEFAULT_FOREGROUND,
             background: DEFAULT_BACKGROUND,
+            graph_color: None,
             position: LogicalPosition::default(),
             mpd_addr: SocketAddr::from_str(DEFAULT_MPD_ADDR)
                 .expect("DEFAULT_MPD_ADDR must be valid"),
+            output: Output::default(),
+            renderer: Renderer::default(),
+            locale: Locale::POSIX,
+            sysfs_root: PathBuf::from(DEFAULT_SYSFS_ROOT).into_boxed_path(),
+            render_png: None,
+            demo: false,
         }
     }
 }
 
+impl Config {
+    /// Parse a configuration in the format of the config file, on top of the defaults.
+    pub fn parse(config: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_with(config, &Registry::default())
+    }
+
+    /// Parse a configuration like [`Config::parse`] does, with the elements in `registry`.
+    pub fn parse_with(
+        config: &str,
+        registry: &Registry,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut parsed = Self::default();
+        parsed.apply(parse_config(config)?, registry)?;
+        Ok(parsed)
+    }
+
+    /// Override the settings that are specified in `cfg`, with the elements in `registry`.
+    fn apply(&mut self, cfg: ConfigBuilder, registry: &Registry) -> Result<(), String> {
+        // TODO: I don't like this pattern, tbh.
+        if let Some(elements) = cfg.elements {
+            self.elements = elements
+                .iter()
+                .map(|elem| registry.parse(elem))
+                .collect::<Result<_, _>>()
+                .map_err(|err| format!("problem encountered while parsing elements: {err}"))?
+        }
+        if let Some(font_path) = cfg.font_path {
+            self.font_path = font_path.into_boxed_path()
+        }
+        if let Some(foreground) = cfg.foreground {
+            self.foreground = foreground
+        }
+        if let Some(background) = cfg.background {
+            self.background = background
+        }
+        if let Some(graph_color) = cfg.graph_color {
+            self.graph_color = Some(graph_color)
+        }
+        if let Some(position) = cfg.position {
+            self.position = LogicalPosition::from(position)
+        }
+        if let Some(mpd_addr) = cfg.mpd_addr {
+            self.mpd_addr = mpd_addr
+        }
+        if let Some(output) = cfg.output {
+            self.output = output
+        }
+        if let Some(renderer) = cfg.renderer {
+            self.renderer = renderer
+        }
+        if let Some(locale) = cfg.locale {
+            self.locale = locale
+        }
+        if let Some(sysfs_root) = cfg.sysfs_root {
+            self.sysfs_root = sysfs_root.into_boxed_path()
+        }
+        if let Some(path) = cfg.render_png {
+            self.render_png = Some(path.into_boxed_path())
+        }
+        if let Some(demo) = cfg.demo {
+            self.demo = demo
+        }
+
+        // The elements and the locale may come from different places, so whenever either changes,
+        // the elements are told about the locale again.
+        for scheduled in &mut self.elements {
+            scheduled.element.set_locale(self.locale)
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 struct ConfigBuilder {
     pub elements: Option<Vec<String>>,
     pub font_path: Option<PathBuf>,
     pub foreground: Option<Pixel>,
     pub background: Option<Pixel>,
+    pub graph_color: Option<Pixel>,
     pub position: Option<(u32, u32)>,
     pub mpd_addr: Option<SocketAddr>,
+    pub output: Option<Output>,
+    pub renderer: Option<Renderer>,
+    pub locale: Option<Locale>,
+    pub sysfs_root: Option<PathBuf>,
+    pub render_png: Option<PathBuf>,
+    pub demo: Option<bool>,
 }
 
 impl ConfigBuilder {
@@ -90,6 +242,10 @@ impl ConfigBuilder {
         self.background = Some(background);
     }
 
+    fn set_graph_color(&mut self, graph_color: Pixel) {
+        self.graph_color = Some(graph_color);
+    }
+
     fn set_position(&mut self, position: (u32, u32)) {
         self.position = Some(position);
     }
@@ -97,6 +253,30 @@ impl ConfigBuilder {
     fn set_mpd_addr(&mut self, mpd_addr: SocketAddr) {
         self.mpd_addr = Some(mpd_addr);
     }
+
+    fn set_output(&mut self, output: Output) {
+        self.output = Some(output);
+    }
+
+    fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = Some(renderer);
+    }
+
+    fn set_locale(&mut self, locale: Locale) {
+        self.locale = Some(locale);
+    }
+
+    fn set_sysfs_root(&mut self, sysfs_root: PathBuf) {
+        self.sysfs_root = Some(sysfs_root);
+    }
+
+    fn set_render_png(&mut self, path: PathBuf) {
+        self.render_png = Some(path);
+    }
+
+    fn set_demo(&mut self, demo: bool) {
+        self.demo = Some(demo);
+    }
 }
 
 fn parse_color(hex: &str) -> Result<u32, String> {
@@ -119,6 +299,12 @@ fn parse_position(pos: &str) -> Result<(u32, u32), String> {
     Ok((x, y))
 }
 
+/// Parse a locale like `nl_NL`, ignoring an encoding like in `nl_NL.UTF-8`.
+fn parse_locale(locale: &str) -> Result<Locale, String> {
+    let name = locale.split(['.', '@']).next().unwrap_or_default();
+    Locale::try_from(name).map_err(|_| format!("unknown locale '{locale}'"))
+}
+
 fn parse_config(config: &str) -> Result<ConfigBuilder, String> {
     let mut cfg = ConfigBuilder::default();
 
@@ -145,16 +331,25 @@ fn parse_config(config: &str) -> Result<ConfigBuilder, String> {
             .ok_or(String::from("expected argument after keyword"))?;
 
         match keyword {
-            "elements" => cfg.set_elements(arguments.iter().map(|s| s.to_string())),
+            "elements" => {
+                // Element arguments may contain spaces, so we split the rest of the line anew.
+                let spec = line[keyword.len()..].trim();
+                cfg.set_elements(split_specification(spec).into_iter().map(String::from))
+            }
             "font_name" => {
                 cfg.set_font_path(PathBuf::from_iter([DEFAULT_FONT_DIR, first_argument]))
             }
             "font_path" => cfg.set_font_path(PathBuf::from(first_argument)),
             "foreground" => cfg.set_foreground(parse_color(first_argument)?.to_be_bytes()),
             "background" => cfg.set_background(parse_color(first_argument)?.to_be_bytes()),
+            "graph_color" => cfg.set_graph_color(parse_color(first_argument)?.to_be_bytes()),
             "position" => cfg.set_position(parse_position(first_argument)?),
             "mpd_addr" => cfg
                 .set_mpd_addr(SocketAddr::from_str(first_argument).map_err(|err| err.to_string())?),
+            "output" => cfg.set_output(first_argument.parse()?),
+            "renderer" => cfg.set_renderer(first_argument.parse()?),
+            "locale" => cfg.set_locale(parse_locale(first_argument)?),
+            "sysfs_root" => cfg.set_sysfs_root(PathBuf::from(first_argument)),
 
             unknown => return Err(format!("unknown keyword '{unknown}'")),
         }
@@ -163,7 +358,7 @@ fn parse_config(config: &str) -> Result<ConfigBuilder, String> {
     Ok(cfg)
 }
 
-fn parse_args() -> Result<ConfigBuilder, lexopt::Error> {
+fn parse_args(registry: &Registry) -> Result<ConfigBuilder, lexopt::Error> {
     let mut cfg = ConfigBuilder::default();
 
     let mut parser = Parser::from_env();
@@ -171,7 +366,7 @@ fn parse_args() -> Result<ConfigBuilder, lexopt::Error> {
         match arg {
             Arg::Long("elements") => {
                 let elems = parser.value()?.string()?;
-                cfg.set_elements(elems.split_whitespace().map(|s| s.to_string()))
+                cfg.set_elements(split_specification(&elems).into_iter().map(String::from))
             }
             Arg::Short('n') | Arg::Long("font-name") => cfg.set_font_path(PathBuf::from_iter([
                 DEFAULT_FONT_DIR,
@@ -188,6 +383,10 @@ fn parse_args() -> Result<ConfigBuilder, lexopt::Error> {
                 let hex = parser.value()?.string()?;
                 cfg.set_background(parse_color(&hex)?.to_be_bytes());
             }
+            Arg::Long("graph-color") => {
+                let hex = parser.value()?.string()?;
+                cfg.set_graph_color(parse_color(&hex)?.to_be_bytes());
+            }
             Arg::Long("position") => {
                 let argument = parser.value()?.string()?;
                 cfg.set_position(parse_position(&argument)?);
@@ -196,12 +395,21 @@ fn parse_args() -> Result<ConfigBuilder, lexopt::Error> {
                 SocketAddr::from_str(&parser.value()?.string()?)
                     .map_err(|err| lexopt::Error::Custom(Box::new(err)))?,
             ),
+            Arg::Long("output") => cfg.set_output(parser.value()?.parse()?),
+            Arg::Long("renderer") => cfg.set_renderer(parser.value()?.parse()?),
+            Arg::Long("locale") => cfg.set_locale(parse_locale(&parser.value()?.string()?)?),
+            Arg::Long("sysfs-root") => cfg.set_sysfs_root(PathBuf::from(parser.value()?)),
+            Arg::Long("render-png") => cfg.set_render_png(PathBuf::from(parser.value()?)),
+            Arg::Long("demo") => cfg.set_demo(true),
             Arg::Short('v') | Arg::Long("version") => {
                 println!("{}", env!("CARGO_PKG_VERSION"));
                 std::process::exit(0);
             }
             Arg::Short('h') | Arg::Long("help") => {
-                usage(parser.bin_name().unwrap_or(env!("CARGO_BIN_NAME")));
+                usage(
+                    parser.bin_name().unwrap_or(env!("CARGO_PKG_NAME")),
+                    registry,
+                );
                 std::process::exit(0);
             }
             _ => return Err(arg.unexpected()),
@@ -214,6 +422,11 @@ fn parse_args() -> Result<ConfigBuilder, lexopt::Error> {
 /// Create a configuration based on defaults, followed by config files, and finally command line
 /// arguments.
 pub fn configure() -> Result<Config, Box<dyn std::error::Error>> {
+    configure_with(&Registry::default())
+}
+
+/// Create a configuration like [`configure`] does, with the elements in `registry`.
+pub fn configure_with(registry: &Registry) -> Result<Config, Box<dyn std::error::Error>> {
     let config_file_path = PathBuf::from_str(CONFIG_FILE_PATH)?;
     let config_file_cfg = match File::open(&config_file_path) {
         Ok(mut config_file) => {
@@ -228,42 +441,22 @@ pub fn configure() -> Result<Config, Box<dyn std::error::Error>> {
             None
         }
     };
-    let command_line_cfg =
-        Some(parse_args().map_err(|err| format!("problem reading command line arguments: {err}"))?);
+    let command_line_cfg = Some(
+        parse_args(registry)
+            .map_err(|err| format!("problem reading command line arguments: {err}"))?,
+    );
 
     let mut config = Config::default();
     for cfg in [config_file_cfg, command_line_cfg].into_iter().flatten() {
-        // TODO: I don't like this pattern, tbh.
-        if let Some(elements) = cfg.elements {
-            config.elements = elements
-                .iter()
-                .map(|elem| Element::from_str(elem))
-                .collect::<Result<_, _>>()
-                .map_err(|err| format!("problem encountered while parsing elements: {err}"))?
-        }
-        if let Some(font_path) = cfg.font_path {
-            config.font_path = font_path.into_boxed_path()
-        }
-        if let Some(foreground) = cfg.foreground {
-            config.foreground = foreground
-        }
-        if let Some(background) = cfg.background {
-            config.background = background
-        }
-        if let Some(position) = cfg.position {
-            config.position = LogicalPosition::from(position)
-        }
-        if let Some(mpd_addr) = cfg.mpd_addr {
-            config.mpd_addr = mpd_addr
-        }
+        config.apply(cfg, registry)?;
     }
 
     Ok(config)
 }
 
-fn usage(bin: &str) {
+fn usage(bin: &str, registry: &Registry) {
     const DESCRIPTION: &str = env!("CARGO_PKG_DESCRIPTION");
-    const BIN: &str = env!("CARGO_BIN_NAME");
+    const BIN: &str = env!("CARGO_PKG_NAME");
     const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
     const VERSION: &str = env!("CARGO_PKG_VERSION");
     const DEFAULT_FG: u32 = u32::from_be_bytes(DEFAULT_FOREGROUND);
@@ -277,11 +470,19 @@ fn usage(bin: &str) {
     eprintln!("    --elements        Define the elements to be displayed.");
     eprintln!("                      This is a space-delimited list of any of the following");
     eprintln!("                      items:");
-    eprintln!("                        - padding(<width>)       - space");
-    eprintln!("                        - date                   - time");
-    eprintln!("                        - label(<text>)          - battery");
-    eprintln!("                        - mem                    - cpu");
-    eprintln!("                        - cpugraph(<width>)      - playbackstate");
+    let mut usages = registry.usages().peekable();
+    while let Some(left) = usages.next() {
+        // Usages that are too wide for the left column get a line of their own.
+        match usages.next_if(|_| left.len() <= 22) {
+            Some(right) => eprintln!("                        - {left:<22} - {right}"),
+            None => eprintln!("                        - {left}"),
+        }
+    }
+    eprintln!("                      Append '@<interval>' to an element to set how often it");
+    eprintln!("                      is updated, such as 'battery@30s' or 'cpu@250ms'.");
+    eprintln!("                      Graphs take a 'style=' of 'bars', 'line', 'dots',");
+    eprintln!("                      'mirror' or 'dither', and graph also takes 'min=<n>'");
+    eprintln!("                      and 'max=<n>', as in 'graph(temp(cpu), 60, max=90)'.");
     eprintln!("    --font-name -n    Set the font name from the default directory.");
     eprintln!("                      (default: '{DEFAULT_FONT}' in '{DEFAULT_FONT_DIR}')");
     eprintln!("    --font-path -p    Set the font path.");
@@ -289,13 +490,66 @@ fn usage(bin: &str) {
     eprintln!("                      (default: {COLOR_PREFIX}{DEFAULT_FG:08x})");
     eprintln!("    --bg              Specify the background color as an rgba hex string.");
     eprintln!("                      (default: {COLOR_PREFIX}{DEFAULT_BG:08x})");
+    eprintln!("    --graph-color     Specify the color of graphs as an rgba hex string.");
+    eprintln!("                      (default: the foreground color)");
     eprintln!("    --position        Set the requested position to spawn the window.");
     eprintln!("                      Must be set as 'x,y' without a space, where x and y are");
     eprintln!("                      unsigned integers.  (default: '0,0')");
     eprintln!("    --mpd-address     Specify the address for the mpd connection.");
     eprintln!("                      (default: {DEFAULT_MPD_ADDR})");
+    eprintln!("    --output          Choose where the bar is sent: 'window', 'i3bar' to write");
+    eprintln!("                      the i3bar JSON protocol to stdout, 'text' to write a");
+    eprintln!("                      line of text to stdout on every update, or");
+    eprintln!("                      'text-inplace' to keep overwriting that line. With");
+    eprintln!("                      'sixel' or 'kitty', the bar is drawn in the terminal");
+    eprintln!("                      using that graphics protocol.  (default: 'window')");
+    eprintln!("    --renderer        Choose how the window is drawn: 'gpu', 'software', or");
+    eprintln!("                      'auto' to fall back to software rendering when the gpu");
+    eprintln!("                      cannot be used.  (default: 'auto')");
+    eprintln!("    --locale          Set the locale for names of weekdays and months, such");
+    eprintln!("                      as 'nl_NL'.  (default: 'POSIX')");
+    eprintln!("    --sysfs-root      Set where sysfs is found, which is read for temperatures");
+    eprintln!("                      and cpu frequencies.  (default: '{DEFAULT_SYSFS_ROOT}')");
+    eprintln!("    --render-png      Render the bar once to a png file at the given path and");
+    eprintln!("                      exit, without opening a window.");
+    eprintln!("    --demo            Show scripted demonstration values instead of actual");
+    eprintln!("                      system information.");
     eprintln!("    --version   -v    Display function.");
     eprintln!("    --help      -h    Display help.");
     eprintln!();
     eprintln!("{BIN} {VERSION} by {AUTHORS}, 2023.");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::testing::psf2_font;
+
+    #[test]
+    fn locale_applies_to_elements() {
+        // The locale applies no matter whether it comes before or after the elements.
+        let glyph_width = psf2_font().determine_width(" ");
+        for config in [
+            "elements date(%a %d %b)\nlocale nl_NL.UTF-8",
+            "locale nl_NL.UTF-8\nelements date(%a %d %b)",
+        ] {
+            let config = Config::parse(config).expect("the config is valid");
+            let width = config.elements[0].element.width(&psf2_font());
+            assert_eq!(width, "ma 01 jan".len() * glyph_width);
+        }
+    }
+
+    #[test]
+    fn registered_elements() {
+        let mut registry = Registry::default();
+        registry.register::<crate::element::Cpu>("processor", "processor");
+
+        // Elements that are registered elsewhere can be used anywhere, and are named after their
+        // registration.
+        let spec = "elements processor graph(processor, 8)";
+        let config = Config::parse_with(spec, &registry).expect("the config is valid");
+        let names: Vec<_> = config.elements.iter().map(|e| e.name).collect();
+        assert_eq!(names, [Some("processor"), Some("graph")]);
+        assert!(Config::parse(spec).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+use std::time::{Duration, Instant};
+
+use crate::font::Font;
+use crate::schedule::{parse_interval, Scheduled};
+use crate::source::{SourceKind, Sources};
+use crate::state::{Block, Draw, State};
+
+mod battery;
+mod bytes;
+mod cpu;
+mod cpucores;
+mod cpufreq;
+mod cpugraph;
+mod cpuheat;
+mod disk;
+mod diskio;
+mod graph;
+mod label;
+mod load;
+mod mem;
+mod net;
+mod netgraph;
+mod padding;
+mod playbackstate;
+mod procs;
+mod space;
+mod swap;
+mod temp;
+mod throughput;
+mod time;
+mod timeformat;
+mod uptime;
+
+pub use battery::Battery;
+pub use cpu::Cpu;
+pub use cpucores::CpuCores;
+pub use cpufreq::{CpuFreq, FrequencySummary};
+pub use cpugraph::CpuGraph;
+pub use cpuheat::CpuHeat;
+pub use disk::Disk;
+pub use diskio::DiskIo;
+pub use graph::{Graph, GraphStyle};
+pub use label::Label;
+pub use load::Load;
+pub use mem::{Mem, MemoryView};
+pub use net::Net;
+pub use netgraph::NetGraph;
+pub use padding::Padding;
+pub use playbackstate::PlaybackState;
+pub use procs::Procs;
+pub use space::Space;
+pub use swap::Swap;
+pub use temp::{Temp, TemperatureUnit};
+pub use time::{kind, Clock, Date, DateAndTime, Time, TimeElement, TimeKind};
+pub use timeformat::TimeFormat;
+pub use uptime::Uptime;
+
+pub type DateTime = chrono::DateTime<chrono::Local>;
+
+/// How often an element is updated, unless it asks for something else.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The number of graph pixels that are represented by a single character in text output, which
+/// shows the highest of them.
+const GRAPH_PIXELS_PER_CHAR: usize = 8;
+
+/// The widest possible rendering of a percentage.
+const PERCENTAGE_TEMPLATE: &str = "000%";
+
+/// The range of a percentage.
+const PERCENTAGE_RANGE: (f32, f32) = (0.0, 100.0);
+
+/// An item in the bar.
+///
+/// Elements are created from the elements specification through a [`Registry`]. They keep their
+/// own data, which they refresh from the [`Sources`] on every update.
+pub trait Element: std::fmt::Debug {
+    /// Create the element from the argument between the parentheses in the specification, if
+    /// there was one.
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError>
+    where
+        Self: Sized;
+
+    /// The width the element takes up in the bar when drawn with `font`.
+    ///
+    /// This should be the width of its widest possible rendering, such that the layout of the bar
+    /// does not shift around.
+    fn width(&self, font: &Font) -> usize;
+
+    /// How the element is aligned within its width.
+    fn alignment(&self) -> Alignment {
+        Alignment::Right
+    }
+
+    /// How often the element would like to be updated, or `None` if it never changes.
+    ///
+    /// This can be overridden in the elements specification, as in `battery@30s`.
+    fn interval(&self) -> Option<Duration> {
+        Some(DEFAULT_INTERVAL)
+    }
+
+    /// Whether the updates should fall right after whole multiples of the interval on the wall
+    /// clock, like the ticks of a clock, rather than at an arbitrary phase.
+    fn aligned(&self) -> bool {
+        false
+    }
+
+    /// Use `locale` for anything that is written in words, such as the names of weekdays.
+    fn set_locale(&mut self, _locale: chrono::Locale) {}
+
+    /// The source the element reads its data from, if any.
+    fn source(&self) -> Option<SourceKind> {
+        None
+    }
+
+    /// Refresh the data of the element from the sources, as of the moment `now`.
+    fn update(&mut self, _sources: &mut Sources, _now: Instant) {}
+
+    /// Refresh the data of the element from the sources outside of its schedule, such as when a
+    /// background source has a new value.
+    ///
+    /// Elements that keep a history of their values should not add to it here, so that it keeps
+    /// one entry per scheduled update.
+    fn refresh(&mut self, sources: &mut Sources, now: Instant) {
+        self.update(sources, now)
+    }
+
+    /// The current value of the element as text, as it is shown in text outputs.
+    ///
+    /// By default, this is also what is drawn. Elements without a textual representation, such as
+    /// padding, return `None`.
+    fn text(&self) -> Option<String> {
+        None
+    }
+
+    /// The current value of the element as a number, such as a percentage or a rate, for graphs.
+    ///
+    /// This is NaN while the value is not known, and `None` for elements without a value, such as
+    /// labels.
+    fn value(&self) -> Option<f32> {
+        None
+    }
+
+    /// The range that the value falls within, if it has one, such as 0 to 100 for a percentage.
+    fn range(&self) -> Option<(f32, f32)> {
+        None
+    }
+
+    /// Whether the element separates the elements around it, rather than showing something.
+    fn is_separator(&self) -> bool {
+        false
+    }
+
+    /// Draw the element, or return `None` to leave its space blank.
+    fn draw(&self, state: &State) -> Option<Block> {
+        self.text().map(|text| text.draw(state))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+}
+
+#[derive(Debug)]
+pub enum ElementParseError {
+    BadInteger(std::num::ParseIntError),
+    UnknownElementName(String),
+    MissingArgument,
+    UnexpectedArgument,
+    BadInterval(String),
+    BadFormat(String),
+    UnknownZone(String),
+    MissingZone,
+    UnknownUnit(String),
+    UnknownOption(String),
+    BadNumber(String),
+    NoValue(String),
+    ZeroWidth,
+    InElement(String, Box<ElementParseError>),
+}
+
+impl std::fmt::Display for ElementParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElementParseError::BadInteger(e) => e.fmt(f),
+            ElementParseError::UnknownElementName(weird) => {
+                write!(f, "unknown element name '{weird}'")
+            }
+            ElementParseError::MissingArgument => write!(f, "expected an argument"),
+            ElementParseError::UnexpectedArgument => write!(f, "did not expect an argument"),
+            ElementParseError::BadInterval(interval) => write!(
+                f,
+                "bad interval '{interval}', expected a positive whole number followed by \
+                'ms', 's', 'm' or 'h'"
+            ),
+            ElementParseError::BadFormat(format) => write!(f, "bad time format '{format}'"),
+            ElementParseError::UnknownZone(zone) => write!(f, "unknown time zone '{zone}'"),
+            ElementParseError::MissingZone => write!(f, "expected a time zone"),
+            ElementParseError::UnknownUnit(unit) => {
+                write!(f, "unknown unit '{unit}', expected 'C' or 'F'")
+            }
+            ElementParseError::UnknownOption(option) => write!(f, "unknown option '{option}'"),
+            ElementParseError::BadNumber(number) => write!(f, "bad number '{number}'"),
+            ElementParseError::NoValue(spec) => write!(f, "'{spec}' has no value to graph"),
+            ElementParseError::ZeroWidth => write!(f, "expected a width of at least 1"),
+            ElementParseError::InElement(spec, err) => write!(f, "in '{spec}': {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ElementParseError {}
+
+impl From<std::num::ParseIntError> for ElementParseError {
+    fn from(value: std::num::ParseIntError) -> Self {
+        Self::BadInteger(value)
+    }
+}
+
+/// Returns the argument, or an error if there is none.
+pub fn required_argument(argument: Option<&str>) -> Result<&str, ElementParseError> {
+    argument.ok_or(ElementParseError::MissingArgument)
+}
+
+/// Returns an error if there is an argument.
+pub fn no_argument(argument: Option<&str>) -> Result<(), ElementParseError> {
+    match argument {
+        Some(_) => Err(ElementParseError::UnexpectedArgument),
+        None => Ok(()),
+    }
+}
+
+/// Parse the width of a graph, which must be at least a single column.
+pub fn parse_width(width: &str) -> Result<usize, ElementParseError> {
+    match width.parse()? {
+        0 => Err(ElementParseError::ZeroWidth),
+        width => Ok(width),
+    }
+}
+
+/// Format a percentage such that it is right-aligned within [`PERCENTAGE_TEMPLATE`].
+fn format_percentage(val: f32) -> String {
+    if val.is_nan() {
+        "---%".to_string()
+    } else {
+        format!("{val:>3.0}%")
+    }
+}
+
+/// Split an elements specification into the specifications of the individual elements.
+///
+/// Elements are separated by whitespace, except within parentheses, such that `date(%a %d %b)` is
+/// a single element.
+pub fn split_specification(spec: &str) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    for (i, ch) in spec.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ch if ch.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    elements.push(&spec[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        elements.push(&spec[start..]);
+    }
+    elements
+}
+
+/// Split the argument of an element into its comma-separated parts, except for commas within
+/// parentheses, such that `temp(package, F), 60` has two parts. The parts are trimmed.
+pub fn split_arguments(argument: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, ch) in argument.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                arguments.push(argument[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(argument[start..].trim());
+    arguments
+}
+
+/// Parses an element from its argument, with the registry at hand for any elements within that
+/// argument.
+pub type ParseFn = fn(Option<&str>, &Registry) -> Result<Box<dyn Element>, ElementParseError>;
+
+struct Entry {
+    name: &'static str,
+    usage: &'static str,
+    parse: ParseFn,
+}
+
+/// The elements that can be used in an elements specification, by name.
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    /// Create a registry without any elements in it.
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Make the element `E` available under `name`.
+    ///
+    /// The `usage` is shown in the usage information, such as `cpugraph(<width>)`. Registering an
+    /// element under an existing name replaces it.
+    pub fn register<E: Element + 'static>(&mut self, name: &'static str, usage: &'static str) {
+        self.register_with(name, usage, |argument, _| Ok(Box::new(E::parse(argument)?)));
+    }
+
+    /// Make an element available under `name` that is created by `parse`.
+    ///
+    /// Unlike [`Registry::register`], this passes the registry along, such that elements like
+    /// `graph` can parse the elements within their argument with it.
+    pub fn register_with(&mut self, name: &'static str, usage: &'static str, parse: ParseFn) {
+        self.entries.retain(|entry| entry.name != name);
+        self.entries.push(Entry { name, usage, parse });
+    }
+
+    /// The usage of every registered element, in order of registration.
+    pub fn usages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|entry| entry.usage)
+    }
+
+    /// Parse a single element from a specification like `time`, `cpugraph(120)` or `battery@30s`.
+    pub fn parse(&self, spec: &str) -> Result<Scheduled, ElementParseError> {
+        self.parse_scheduled(spec)
+            .map_err(|err| ElementParseError::InElement(spec.to_string(), Box::new(err)))
+    }
+
+    fn parse_scheduled(&self, spec: &str) -> Result<Scheduled, ElementParseError> {
+        // Any element can be given an interval. An '@' within the argument, as in
+        // `label(me@host)`, is part of that argument instead.
+        let (element, interval) = match spec.rsplit_once('@') {
+            Some((element, interval)) if !interval.contains(')') => {
+                (element, Some(parse_interval(interval)?))
+            }
+            _ => (spec, None),
+        };
+
+        // Some elements take a user-specifiable argument, which may itself end in a ')', as in
+        // `graph(cpu(0), 60)`.
+        let (name, argument) = match element
+            .split_once('(')
+            .and_then(|(name, argument)| Some((name, argument.strip_suffix(')')?)))
+        {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (element, None),
+        };
+
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| ElementParseError::UnknownElementName(name.to_string()))?;
+        let element = (entry.parse)(argument, self)?;
+        let interval = interval.or_else(|| element.interval());
+        Ok(Scheduled {
+            element,
+            interval,
+            name: Some(entry.name),
+        })
+    }
+}
+
+impl Default for Registry {
+    /// Create a registry with all of the built-in elements.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register::<Padding>("padding", "padding(<width>)");
+        registry.register::<Space>("space", "space");
+        registry.register::<Date>("date", "date[(<format>)]");
+        registry.register::<Time>("time", "time[(<format>)]");
+        registry.register::<DateAndTime>("datetime", "datetime[(<format>)]");
+        registry.register::<Clock>("clock", "clock(<zone>[, <format>])");
+        registry.register::<Label>("label", "label(<text>)");
+        registry.register::<Battery>("battery", "battery");
+        registry.register::<Mem>("mem", "mem[(abs|avail|cache)]");
+        registry.register::<Swap>("swap", "swap[(abs|avail)]");
+        registry.register::<Cpu>("cpu", "cpu[(<n>)]");
+        registry.register::<CpuCores>("cpucores", "cpucores");
+        registry.register::<CpuGraph>("cpugraph", "cpugraph(<width>[, <options>])");
+        registry.register::<CpuHeat>("cpuheat", "cpuheat(<width>)");
+        registry.register::<CpuFreq>("cpufreq", "cpufreq[(max|avg[, gov])]");
+        registry.register::<PlaybackState>("playbackstate", "playbackstate");
+        registry.register::<Net>("net", "net(<interface>)");
+        registry.register::<NetGraph>("netgraph", "netgraph(<interface>, <width>)");
+        registry.register::<Disk>("disk", "disk(<mount point>)");
+        registry.register::<DiskIo>("diskio", "diskio(<device>)");
+        registry.register::<Temp>("temp", "temp(<label>[, C|F])");
+        registry.register::<Load>("load", "load");
+        registry.register::<Uptime>("uptime", "uptime[(<format>)]");
+        registry.register::<Procs>("procs", "procs");
+        registry.register_with(
+            "graph",
+            "graph(<source>, <width>[, <options>])",
+            |argument, registry| Ok(Box::new(Graph::parse_with(argument, registry)?)),
+        );
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_elements_specification() {
+        assert_eq!(
+            split_specification("  time(%H:%M)  date(%a %d %b) label(a (b) c)\tcpu "),
+            ["time(%H:%M)", "date(%a %d %b)", "label(a (b) c)", "cpu"]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use super::{
+    format_percentage, no_argument, Element, ElementParseError, PERCENTAGE_RANGE,
+    PERCENTAGE_TEMPLATE,
+};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+const BATTERY_FULL_PERCENTAGE: f32 = 98.0;
+
+/// The state of charge changes slowly, and asking for it can be expensive.
+const INTERVAL: Duration = Duration::from_secs(30);
+
+/// The battery state of charge as a percentage.
+#[derive(Debug, Clone, Default)]
+pub struct Battery(pub f32);
+
+impl Element for Battery {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        no_argument(argument)?;
+        Ok(Self::default())
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(PERCENTAGE_TEMPLATE)
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(INTERVAL)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Battery)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        if let Some(bat) = &mut sources.battery {
+            bat.refresh();
+            self.0 = bat.state_of_charge();
+            // If the battery is basically full, just set it to 100%.
+            if self.0 > BATTERY_FULL_PERCENTAGE {
+                self.0 = 100.0
+            }
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(format_percentage(self.0))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.0)
+    }
+
+    fn range(&self) -> Option<(f32, f32)> {
+        Some(PERCENTAGE_RANGE)
+    }
+}
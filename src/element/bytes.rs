@@ -0,0 +1,43 @@
+//! Formatting amounts of bytes, such as memory sizes and rates, in a width that can be bounded.
+
+use crate::font::Font;
+
+const UNITS: [char; 5] = ['B', 'K', 'M', 'G', 'T'];
+
+/// Format a number of bytes in four characters, such as ` 12K`, `1.5M` or ` 16G`.
+///
+/// The units are powers of 1024.
+pub fn format_bytes(mut bytes: f64) -> String {
+    let mut unit = 0;
+    while bytes >= 999.5 && unit < UNITS.len() - 1 {
+        bytes /= 1024.0;
+        unit += 1;
+    }
+    if unit > 0 && bytes < 9.95 {
+        format!("{bytes:.1}{}", UNITS[unit])
+    } else {
+        format!("{bytes:>3.0}{}", UNITS[unit])
+    }
+}
+
+/// Format a pair of numbers of bytes like `5.2G/ 16G`, or dashes if they are not known.
+pub fn format_pair(pair: Option<(f64, f64)>) -> String {
+    match pair {
+        Some((a, b)) => format!("{}/{}", format_bytes(a), format_bytes(b)),
+        None => "----/----".to_string(),
+    }
+}
+
+/// The width of the widest text [`format_bytes`] can produce.
+pub fn bytes_width(font: &Font) -> usize {
+    UNITS
+        .map(|unit| font.determine_width(&format!("000{unit}")))
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+}
+
+/// The width of the widest text [`format_pair`] can produce.
+pub fn pair_width(font: &Font) -> usize {
+    bytes_width(font) + font.determine_width("/") + bytes_width(font)
+}
@@ -0,0 +1,63 @@
+use std::time::Instant;
+
+use super::{format_percentage, Element, ElementParseError, PERCENTAGE_RANGE, PERCENTAGE_TEMPLATE};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources, SystemSource};
+
+/// The cpu usage as a percentage, of a single cpu or averaged over all of them.
+#[derive(Debug, Clone, Default)]
+pub struct Cpu {
+    /// The index of the cpu, counting from 0, or `None` for all of them.
+    pub core: Option<usize>,
+    pub usage: f32,
+}
+
+/// Returns the usage averaged over all cpus.
+pub(super) fn average_usage(sys: &dyn SystemSource) -> f32 {
+    let cpus = sys.cpu_usages();
+    cpus.iter().sum::<f32>() / cpus.len() as f32
+}
+
+impl Element for Cpu {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        let core = match argument {
+            Some(core) => Some(core.trim().parse()?),
+            None => None,
+        };
+        Ok(Self { core, usage: 0.0 })
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(PERCENTAGE_TEMPLATE)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Cpu)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        // FIXME: Sometimes on (at least) macOS, this returns NaN. This would crash the program, so
+        // we have a NaN check when formatting the element.
+        self.usage = match self.core {
+            Some(core) => sources
+                .sys
+                .cpu_usages()
+                .get(core)
+                .copied()
+                .unwrap_or(f32::NAN),
+            None => average_usage(sources.sys.as_ref()),
+        };
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(format_percentage(self.usage))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.usage)
+    }
+
+    fn range(&self) -> Option<(f32, f32)> {
+        Some(PERCENTAGE_RANGE)
+    }
+}
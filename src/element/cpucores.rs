@@ -0,0 +1,75 @@
+use std::time::Instant;
+
+use super::{no_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+use crate::state::{sparkline_char, Block, State};
+
+/// The width of the bar of each cpu, in pixels.
+const BAR_WIDTH: usize = 2;
+/// The space between the bars, in pixels.
+const BAR_GAP: usize = 1;
+
+/// A tiny bar for the usage of each cpu.
+///
+/// The number of bars is that of the cpus at the first update, and has no width before then.
+#[derive(Debug, Clone, Default)]
+pub struct CpuCores(pub Vec<f32>);
+
+impl CpuCores {
+    /// Create the element for `cores` cpus.
+    pub fn new(cores: usize) -> Self {
+        Self(vec![0.0; cores])
+    }
+}
+
+impl Element for CpuCores {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        no_argument(argument)?;
+        Ok(Self::default())
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        (self.0.len() * (BAR_WIDTH + BAR_GAP)).saturating_sub(BAR_GAP)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Cpu)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        let mut usages = sources.sys.cpu_usages();
+        // After the first update, the number of bars stays the same, so that the width does not
+        // change.
+        if !self.0.is_empty() {
+            usages.resize(self.0.len(), f32::NAN);
+        }
+        self.0 = usages;
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.0.iter().map(|&usage| sparkline_char(usage)).collect())
+    }
+
+    fn draw(&self, state: &State) -> Option<Block> {
+        // Before the first update, there are no bars to draw.
+        if self.0.is_empty() {
+            return None;
+        }
+        let height = state.font.height();
+        let width = self.width(&state.font);
+        let mut pixels = vec![state.background; height * width];
+
+        for (core, usage) in self.0.iter().enumerate() {
+            let filled = ((usage / 100.0 * height as f32) as usize).min(height);
+            let x0 = core * (BAR_WIDTH + BAR_GAP);
+            for y in height - filled..height {
+                for x in x0..x0 + BAR_WIDTH {
+                    pixels[y * width + x] = state.graph_color;
+                }
+            }
+        }
+
+        Some(Block::new(height, pixels))
+    }
+}
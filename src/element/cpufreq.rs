@@ -0,0 +1,105 @@
+use std::time::Instant;
+
+use super::{Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The number of characters that the governor takes up, which fits each of the governors that
+/// come with the kernel. Longer ones are cut short.
+const GOVERNOR_LEN: usize = "conservative".len();
+
+/// How the frequencies of all cpus are summarized into one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencySummary {
+    #[default]
+    Average,
+    Max,
+}
+
+/// The frequency of the cpus, like `2.4G`, optionally followed by the governor.
+#[derive(Debug, Clone, Default)]
+pub struct CpuFreq {
+    pub summary: FrequencySummary,
+    pub show_governor: bool,
+    /// The frequency in MHz, if it is known.
+    pub frequency: Option<u64>,
+    pub governor: Option<String>,
+}
+
+/// Format a frequency in MHz in four characters, such as `2.4G` or `800M`.
+fn format_frequency(mhz: Option<u64>) -> String {
+    match mhz {
+        None => "-.-G".to_string(),
+        Some(mhz) if mhz < 1000 => format!("{mhz:>3}M"),
+        Some(mhz) if mhz < 9950 => format!("{:.1}G", mhz as f32 / 1000.0),
+        Some(mhz) => format!("{:>3.0}G", mhz as f32 / 1000.0),
+    }
+}
+
+impl Element for CpuFreq {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        let mut cpufreq = Self::default();
+        for option in argument
+            .into_iter()
+            .flat_map(|argument| argument.split(','))
+        {
+            match option.trim() {
+                "avg" => cpufreq.summary = FrequencySummary::Average,
+                "max" => cpufreq.summary = FrequencySummary::Max,
+                "gov" => cpufreq.show_governor = true,
+                weird => return Err(ElementParseError::UnknownOption(weird.to_string())),
+            }
+        }
+        Ok(cpufreq)
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        let frequency = ["0.0G", "000M"]
+            .map(|template| font.determine_width(template))
+            .into_iter()
+            .max()
+            .unwrap_or_default();
+        if !self.show_governor {
+            return frequency;
+        }
+        // Governors are named in lowercase letters, so there is room for that many of the
+        // widest one.
+        let letter = ('a'..='z')
+            .map(|letter| font.determine_width(&letter.to_string()))
+            .max()
+            .unwrap_or_default();
+        frequency + font.determine_width(" ") + letter * GOVERNOR_LEN
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Frequency)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        let frequencies = sources.frequency.frequencies();
+        self.frequency = match self.summary {
+            _ if frequencies.is_empty() => None,
+            FrequencySummary::Average => {
+                Some(frequencies.iter().sum::<u64>() / frequencies.len() as u64)
+            }
+            FrequencySummary::Max => frequencies.iter().max().copied(),
+        };
+        self.governor = sources.frequency.governor();
+    }
+
+    fn text(&self) -> Option<String> {
+        let frequency = format_frequency(self.frequency);
+        if !self.show_governor {
+            return Some(frequency);
+        }
+        let governor: String = match &self.governor {
+            Some(governor) => governor.chars().take(GOVERNOR_LEN).collect(),
+            None => "?".to_string(),
+        };
+        Some(format!("{frequency} {governor:<GOVERNOR_LEN$}"))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.frequency.map_or(f32::NAN, |mhz| mhz as f32))
+    }
+}
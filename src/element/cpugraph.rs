@@ -0,0 +1,64 @@
+use std::time::Instant;
+
+use super::cpu::average_usage;
+use super::graph::GraphStyle;
+use super::{
+    parse_width, required_argument, split_arguments, Element, ElementParseError,
+    GRAPH_PIXELS_PER_CHAR,
+};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+use crate::state::{Block, History, State};
+
+/// A graph of the cpu usage over time, one pixel column per update.
+#[derive(Debug, Clone, Default)]
+pub struct CpuGraph {
+    pub history: History<f32>,
+    pub style: GraphStyle,
+}
+
+impl CpuGraph {
+    pub fn new(width: usize) -> Self {
+        Self {
+            history: History::new(width),
+            style: GraphStyle::default(),
+        }
+    }
+}
+
+impl Element for CpuGraph {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        let arguments = split_arguments(required_argument(argument)?);
+        let (width, options) = arguments
+            .split_first()
+            .expect("there is always a first part");
+        let mut graph = Self::new(parse_width(width)?);
+        for option in options {
+            match option.split_once('=') {
+                Some(("style", style)) => graph.style = GraphStyle::parse(style.trim())?,
+                _ => return Err(ElementParseError::UnknownOption(option.to_string())),
+            }
+        }
+        Ok(graph)
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        self.history.len()
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Cpu)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.history.push(average_usage(sources.sys.as_ref()));
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.history.sparkline(GRAPH_PIXELS_PER_CHAR))
+    }
+
+    fn draw(&self, state: &State) -> Option<Block> {
+        Some(self.style.draw(&self.history, state))
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Instant;
+
+use super::{parse_width, required_argument, Element, ElementParseError, GRAPH_PIXELS_PER_CHAR};
+use crate::config::Pixel;
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+use crate::state::{Block, History, State};
+
+/// A graph of the usage of each cpu over time, one pixel column per update.
+///
+/// The cpus are spread over the rows, from the first at the top to the last at the bottom, and
+/// the busier a cpu is, the closer its pixels are to the graph color. When there are more cpus
+/// than rows, each row shows the busiest of its cpus.
+#[derive(Debug, Clone, Default)]
+pub struct CpuHeat(pub History<Vec<f32>>);
+
+/// Mix `from` and `to`, where `t` goes from 0 for `from` to 1 for `to`.
+fn blend(from: Pixel, to: Pixel, t: f32) -> Pixel {
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+    std::array::from_fn(|i| (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t).round() as u8)
+}
+
+impl Element for CpuHeat {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Ok(Self(History::new(parse_width(required_argument(
+            argument,
+        )?)?)))
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        self.0.len()
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Cpu)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.0.push(sources.sys.cpu_usages());
+    }
+
+    fn text(&self) -> Option<String> {
+        // A line of text cannot show every cpu, so it shows the busiest one.
+        let busiest = self
+            .0
+            .map(|usages| usages.iter().copied().fold(0.0, f32::max));
+        Some(busiest.sparkline(GRAPH_PIXELS_PER_CHAR))
+    }
+
+    fn draw(&self, state: &State) -> Option<Block> {
+        let height = state.font.height();
+        let width = self.0.len();
+        let mut pixels = vec![state.background; height * width];
+
+        for (x, usages) in self.0.iter().enumerate() {
+            if usages.is_empty() {
+                continue;
+            }
+            for y in 0..height {
+                let first = y * usages.len() / height;
+                let last = ((y + 1) * usages.len() / height).max(first + 1);
+                let usage = usages[first..last].iter().copied().fold(0.0, f32::max);
+                pixels[y * width + x] = blend(state.background, state.graph_color, usage / 100.0);
+            }
+        }
+
+        Some(Block::new(height, pixels))
+    }
+}
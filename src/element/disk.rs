@@ -0,0 +1,58 @@
+use std::time::Instant;
+
+use super::{
+    format_percentage, required_argument, Element, ElementParseError, PERCENTAGE_RANGE,
+    PERCENTAGE_TEMPLATE,
+};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The space used on a disk as a percentage.
+#[derive(Debug, Clone)]
+pub struct Disk {
+    pub mount_point: String,
+    /// The used space as a percentage, which is NaN if nothing is mounted at the mount point.
+    pub usage: f32,
+}
+
+impl Disk {
+    pub fn new(mount_point: &str) -> Self {
+        Self {
+            mount_point: mount_point.to_string(),
+            usage: f32::NAN,
+        }
+    }
+}
+
+impl Element for Disk {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Ok(Self::new(required_argument(argument)?.trim()))
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(PERCENTAGE_TEMPLATE)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Disk)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.usage = match sources.sys.disk_usage(&self.mount_point) {
+            Some((used, total)) => used as f32 / total as f32 * 100.0,
+            None => f32::NAN,
+        };
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(format_percentage(self.usage))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.usage)
+    }
+
+    fn range(&self) -> Option<(f32, f32)> {
+        Some(PERCENTAGE_RANGE)
+    }
+}
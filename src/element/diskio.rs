@@ -0,0 +1,57 @@
+use std::time::Instant;
+
+use super::bytes::{format_pair, pair_width};
+use super::throughput::{combined, Throughput};
+use super::{required_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The rates at which a block device is read from and written to, like ` 40M/3.0M`.
+#[derive(Debug, Clone)]
+pub struct DiskIo {
+    /// The name of the device, such as `sda` or `nvme0n1`.
+    pub device: String,
+    /// The bytes read and written per second, once they are known.
+    pub rates: Option<(f32, f32)>,
+    throughput: Throughput,
+}
+
+impl DiskIo {
+    pub fn new(device: &str) -> Self {
+        Self {
+            device: device.to_string(),
+            rates: None,
+            throughput: Throughput::default(),
+        }
+    }
+}
+
+impl Element for DiskIo {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        // The device may be given by its path, as in `/dev/sda`.
+        let device = required_argument(argument)?.trim();
+        Ok(Self::new(device.strip_prefix("/dev/").unwrap_or(device)))
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        pair_width(font)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Disk)
+    }
+
+    fn update(&mut self, sources: &mut Sources, now: Instant) {
+        let totals = sources.sys.disk_io_totals(&self.device);
+        self.rates = self.throughput.update(now, totals);
+    }
+
+    fn text(&self) -> Option<String> {
+        let rates = self.rates.map(|(a, b)| (f64::from(a), f64::from(b)));
+        Some(format_pair(rates))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(combined(self.rates))
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::{Duration, Instant};
+
+use super::{
+    parse_width, required_argument, split_arguments, Element, ElementParseError, Registry,
+    GRAPH_PIXELS_PER_CHAR,
+};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+use crate::state::{Block, History, State};
+
+/// A graph of the value of any other element over time, one pixel column per update, such as
+/// `graph(temp(package), 60)`.
+///
+/// Percentages and other values with a known range fill the graph from the bottom of their range
+/// to the top. Other values, such as rates, scale to the highest value in the graph, unless a
+/// `min` or `max` is given.
+#[derive(Debug)]
+pub struct Graph {
+    /// The element whose value is graphed.
+    pub source: Box<dyn Element>,
+    /// How often the source is updated, if that was given in its specification.
+    interval: Option<Duration>,
+    pub history: History<f32>,
+    /// The value at the bottom of the graph.
+    pub min: Option<f32>,
+    /// The value at the top of the graph.
+    pub max: Option<f32>,
+    pub style: GraphStyle,
+}
+
+impl Graph {
+    /// The values at the bottom and at the top of the graph.
+    fn bounds(&self) -> (f32, f32) {
+        let range = self.source.range();
+        let bottom = self.min.or(range.map(|(low, _)| low)).unwrap_or(0.0);
+        let top = self.max.or(range.map(|(_, high)| high)).unwrap_or_else(|| {
+            // NaN's are ignored by max.
+            self.history.iter().fold(bottom, |a, &b| a.max(b))
+        });
+        (bottom, top)
+    }
+
+    /// The values in the graph as percentages of the height.
+    fn levels(&self) -> History<f32> {
+        let (bottom, top) = self.bounds();
+        let span = top - bottom;
+        self.history.map(|&value| {
+            if span > 0.0 {
+                (value - bottom) / span * 100.0
+            } else {
+                0.0
+            }
+        })
+    }
+}
+
+/// Parse a number like the `20` in `min=20`.
+fn parse_number(number: &str) -> Result<f32, ElementParseError> {
+    number
+        .parse()
+        .map_err(|_| ElementParseError::BadNumber(number.to_string()))
+}
+
+impl Graph {
+    /// Parse the argument of a graph, in which the element that is graphed is looked up in
+    /// `registry`.
+    pub fn parse_with(
+        argument: Option<&str>,
+        registry: &Registry,
+    ) -> Result<Self, ElementParseError> {
+        let arguments = split_arguments(required_argument(argument)?);
+        let [spec, width, options @ ..] = arguments.as_slice() else {
+            return Err(ElementParseError::MissingArgument);
+        };
+
+        let scheduled = registry.parse(spec)?;
+        if scheduled.element.value().is_none() {
+            return Err(ElementParseError::NoValue(spec.to_string()));
+        }
+
+        let mut graph = Self {
+            source: scheduled.element,
+            interval: scheduled.interval,
+            history: History::new(parse_width(width)?),
+            min: None,
+            max: None,
+            style: GraphStyle::default(),
+        };
+        for option in options {
+            match option.split_once('=') {
+                Some(("min", min)) => graph.min = Some(parse_number(min.trim())?),
+                Some(("max", max)) => graph.max = Some(parse_number(max.trim())?),
+                Some(("style", style)) => graph.style = GraphStyle::parse(style.trim())?,
+                _ => return Err(ElementParseError::UnknownOption(option.to_string())),
+            }
+        }
+        Ok(graph)
+    }
+}
+
+impl Element for Graph {
+    /// Parse the argument of a graph of one of the built-in elements.
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Self::parse_with(argument, &Registry::default())
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        self.history.len()
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        self.source.source()
+    }
+
+    fn update(&mut self, sources: &mut Sources, now: Instant) {
+        self.source.update(sources, now);
+        self.history.push(self.source.value().unwrap_or(f32::NAN));
+    }
+
+    fn refresh(&mut self, sources: &mut Sources, now: Instant) {
+        // The history only moves along with the schedule.
+        self.source.refresh(sources, now);
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.levels().sparkline(GRAPH_PIXELS_PER_CHAR))
+    }
+
+    fn draw(&self, state: &State) -> Option<Block> {
+        Some(self.style.draw(&self.levels(), state))
+    }
+}
+
+/// The thresholds for an ordered dither, in sixteenths, which spread the lit pixels of any level
+/// evenly over a 4 by 4 tile.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How a graph is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GraphStyle {
+    /// Columns that are filled in from the bottom.
+    #[default]
+    Bars,
+    /// A line of a single pixel, connected from column to column.
+    Line,
+    /// A single pixel in each column, at its level.
+    Dots,
+    /// Columns that grow out from the middle.
+    Mirror,
+    /// Columns of the full height, with more pixels lit the higher the level.
+    Dither,
+}
+
+impl GraphStyle {
+    /// Parse a style by name, like the `line` in `style=line`.
+    pub(super) fn parse(style: &str) -> Result<Self, ElementParseError> {
+        match style {
+            "bars" => Ok(Self::Bars),
+            "line" => Ok(Self::Line),
+            "dots" => Ok(Self::Dots),
+            "mirror" => Ok(Self::Mirror),
+            "dither" => Ok(Self::Dither),
+            weird => Err(ElementParseError::UnknownOption(format!("style={weird}"))),
+        }
+    }
+
+    /// Draw a graph of `levels`, which are percentages of the height, newest on the left.
+    pub(super) fn draw(&self, levels: &History<f32>, state: &State) -> Block {
+        let height = state.font.height();
+        let width = levels.len();
+        let mut pixels = vec![state.background; height * width];
+
+        // The row of the point in the previous column, for lines to connect to.
+        let mut previous: Option<usize> = None;
+        for (x, &level) in levels.iter().enumerate() {
+            let fraction = level / 100.0;
+            let filled = ((fraction * height as f32) as usize).min(height);
+            // Unknown values have no point, which breaks up a line.
+            let point = (!fraction.is_nan())
+                .then(|| ((1.0 - fraction.clamp(0.0, 1.0)) * (height - 1) as f32).round() as usize);
+            let lit = |y: usize| match self {
+                GraphStyle::Bars => y >= height - filled,
+                GraphStyle::Dots => point == Some(y),
+                GraphStyle::Line => match (point, previous) {
+                    // The line runs up or down to just short of the previous point.
+                    (Some(point), Some(previous)) => {
+                        y == point || (previous.min(point) < y && y < previous.max(point))
+                    }
+                    (point, _) => point == Some(y),
+                },
+                GraphStyle::Mirror => {
+                    let top = (height - filled) / 2;
+                    (top..top + filled).contains(&y)
+                }
+                GraphStyle::Dither => fraction * 16.0 > f32::from(BAYER[y % 4][x % 4]) + 0.5,
+            };
+            for y in (0..height).filter(|&y| lit(y)) {
+                pixels[y * width + x] = state.graph_color;
+            }
+            previous = point;
+        }
+
+        Block::new(height, pixels)
+    }
+}
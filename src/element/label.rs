@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use super::{required_argument, Element, ElementParseError};
+use crate::font::Font;
+
+/// A fixed piece of text.
+#[derive(Debug, Clone)]
+pub struct Label(pub String);
+
+impl Element for Label {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Ok(Self(required_argument(argument)?.to_string()))
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(&self.0)
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.0.clone())
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+
+use super::{no_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The widest possible rendering of the load averages.
+const LOAD_TEMPLATE: &str = "0.00 0.00 0.00";
+
+/// The load averages over the last 1, 5 and 15 minutes.
+#[derive(Debug, Clone, Default)]
+pub struct Load(pub [f64; 3]);
+
+/// Format a load average in four characters, with fewer decimals as it gets higher.
+fn format_load(load: f64) -> String {
+    match load {
+        load if load < 9.995 => format!("{load:.2}"),
+        load if load < 99.95 => format!("{load:.1}"),
+        load => format!("{load:>4.0}"),
+    }
+}
+
+impl Element for Load {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        no_argument(argument)?;
+        Ok(Self::default())
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(LOAD_TEMPLATE)
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        // The kernel only works out the load averages every five seconds.
+        Some(Duration::from_secs(5))
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::System)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.0 = sources.sys.load_average();
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.0.map(format_load).join(" "))
+    }
+
+    fn value(&self) -> Option<f32> {
+        // The most recent of the load averages.
+        Some(self.0[0] as f32)
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::Instant;
+
+use super::bytes::{bytes_width, format_bytes, format_pair, pair_width};
+use super::{format_percentage, Element, ElementParseError, PERCENTAGE_RANGE, PERCENTAGE_TEMPLATE};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// What is shown of the memory or swap.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryView {
+    /// The used part as a percentage, like ` 33%`.
+    #[default]
+    Percentage,
+    /// The used and total amount, like `5.2G/ 16G`.
+    Absolute,
+    /// The amount that is available, like ` 10G`.
+    Available,
+    /// The amounts used for buffers and for the page cache, like `312M/4.1G`.
+    Cache,
+}
+
+impl MemoryView {
+    /// Parse the argument of a memory element, which is either nothing for a percentage, or one of
+    /// the `views` by name.
+    pub(super) fn parse(
+        argument: Option<&str>,
+        views: &[MemoryView],
+    ) -> Result<Self, ElementParseError> {
+        let Some(argument) = argument else {
+            return Ok(Self::Percentage);
+        };
+        let view = match argument.trim() {
+            "abs" => Self::Absolute,
+            "avail" => Self::Available,
+            "cache" => Self::Cache,
+            weird => return Err(ElementParseError::UnknownOption(weird.to_string())),
+        };
+        if !views.contains(&view) {
+            return Err(ElementParseError::UnknownOption(
+                argument.trim().to_string(),
+            ));
+        }
+        Ok(view)
+    }
+
+    /// The width of the widest text this view can produce.
+    pub(super) fn width(&self, font: &Font) -> usize {
+        match self {
+            MemoryView::Percentage => font.determine_width(PERCENTAGE_TEMPLATE),
+            MemoryView::Absolute | MemoryView::Cache => pair_width(font),
+            MemoryView::Available => bytes_width(font),
+        }
+    }
+
+    /// Format the `used` and `total` amounts in bytes, and the amounts in `cache`, according to
+    /// this view.
+    pub(super) fn format(&self, used: u64, total: u64, cache: Option<(u64, u64)>) -> String {
+        match self {
+            MemoryView::Percentage => format_percentage(used as f32 / total as f32 * 100.0),
+            MemoryView::Absolute => format_pair(Some((used as f64, total as f64))),
+            MemoryView::Available => format_bytes(total.saturating_sub(used) as f64),
+            MemoryView::Cache => format_pair(cache.map(|(a, b)| (a as f64, b as f64))),
+        }
+    }
+
+    /// The number that is shown in this view, where the amounts in `cache` are added up.
+    pub(super) fn value(&self, used: u64, total: u64, cache: Option<(u64, u64)>) -> f32 {
+        match self {
+            MemoryView::Percentage => used as f32 / total as f32 * 100.0,
+            MemoryView::Absolute => used as f32,
+            MemoryView::Available => total.saturating_sub(used) as f32,
+            MemoryView::Cache => cache.map_or(f32::NAN, |(a, b)| (a + b) as f32),
+        }
+    }
+
+    /// The range of the number that is shown in this view, given the `total` amount in bytes.
+    pub(super) fn range(&self, total: u64) -> (f32, f32) {
+        match self {
+            MemoryView::Percentage => PERCENTAGE_RANGE,
+            _ => (0.0, total as f32),
+        }
+    }
+}
+
+/// The memory usage, as a percentage by default.
+#[derive(Debug, Clone, Default)]
+pub struct Mem {
+    pub view: MemoryView,
+    /// The used memory in bytes, which counts everything that is not available.
+    pub used: u64,
+    /// The total memory in bytes.
+    pub total: u64,
+    /// The memory used for buffers and for the page cache in bytes, if that is known.
+    pub buffers_and_cache: Option<(u64, u64)>,
+}
+
+impl Element for Mem {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        use MemoryView::*;
+        Ok(Self {
+            view: MemoryView::parse(argument, &[Percentage, Absolute, Available, Cache])?,
+            ..Default::default()
+        })
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        self.view.width(font)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Memory)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.used = sources.sys.used_memory();
+        self.total = sources.sys.total_memory();
+        // Reading these takes another trip to the system, so only do so when they are shown.
+        if self.view == MemoryView::Cache {
+            self.buffers_and_cache = sources.sys.buffers_and_cache();
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(
+            self.view
+                .format(self.used, self.total, self.buffers_and_cache),
+        )
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(
+            self.view
+                .value(self.used, self.total, self.buffers_and_cache),
+        )
+    }
+
+    fn range(&self) -> Option<(f32, f32)> {
+        Some(self.view.range(self.total))
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Instant;
+
+use super::bytes::{format_pair, pair_width};
+use super::throughput::{combined, Throughput};
+use super::{required_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The rates at which a network interface receives and transmits, like ` 12K/1.5M`.
+#[derive(Debug, Clone)]
+pub struct Net {
+    pub interface: String,
+    /// The received and transmitted bytes per second, once they are known.
+    pub rates: Option<(f32, f32)>,
+    throughput: Throughput,
+}
+
+impl Net {
+    pub fn new(interface: &str) -> Self {
+        Self {
+            interface: interface.to_string(),
+            rates: None,
+            throughput: Throughput::default(),
+        }
+    }
+}
+
+impl Element for Net {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Ok(Self::new(required_argument(argument)?.trim()))
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        pair_width(font)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Network)
+    }
+
+    fn update(&mut self, sources: &mut Sources, now: Instant) {
+        let totals = sources.sys.network_totals(&self.interface);
+        self.rates = self.throughput.update(now, totals);
+    }
+
+    fn text(&self) -> Option<String> {
+        let rates = self.rates.map(|(a, b)| (f64::from(a), f64::from(b)));
+        Some(format_pair(rates))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(combined(self.rates))
+    }
+}
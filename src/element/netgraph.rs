@@ -0,0 +1,115 @@
+use std::time::Instant;
+
+use super::throughput::Throughput;
+use super::{parse_width, required_argument, Element, ElementParseError, GRAPH_PIXELS_PER_CHAR};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+use crate::state::{Block, History, State};
+
+/// The lowest rate in bytes per second that the graph scales to, such that a quiet connection
+/// does not look busy.
+const MIN_SCALE: f32 = 1024.0;
+
+/// A graph of the rates at which a network interface receives and transmits, one pixel column
+/// per update. It scales to the highest rate it shows.
+#[derive(Debug, Clone)]
+pub struct NetGraph {
+    pub interface: String,
+    /// The received bytes per second.
+    pub rx: History<f32>,
+    /// The transmitted bytes per second.
+    pub tx: History<f32>,
+    throughput: Throughput,
+}
+
+impl NetGraph {
+    pub fn new(interface: &str, width: usize) -> Self {
+        Self {
+            interface: interface.to_string(),
+            rx: History::new(width),
+            tx: History::new(width),
+            throughput: Throughput::default(),
+        }
+    }
+
+    /// The rate that reaches the top of the graph.
+    fn scale(&self) -> f32 {
+        self.rx
+            .iter()
+            .chain(self.tx.iter())
+            .fold(MIN_SCALE, |a, &b| a.max(b))
+    }
+}
+
+impl Element for NetGraph {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        let (interface, width) = required_argument(argument)?
+            .split_once(',')
+            .ok_or(ElementParseError::MissingArgument)?;
+        Ok(Self::new(interface.trim(), parse_width(width.trim())?))
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        self.rx.len()
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Network)
+    }
+
+    fn update(&mut self, sources: &mut Sources, now: Instant) {
+        let totals = sources.sys.network_totals(&self.interface);
+        // Until the rates are known, we act as if nothing is happening.
+        let (rx, tx) = self.throughput.update(now, totals).unwrap_or_default();
+        self.rx.push(rx);
+        self.tx.push(tx);
+    }
+
+    fn text(&self) -> Option<String> {
+        let scale = self.scale();
+        let line = |hist: &History<f32>| {
+            hist.map(|rate| rate / scale * 100.0)
+                .sparkline(GRAPH_PIXELS_PER_CHAR)
+        };
+        Some(format!("{}/{}", line(&self.rx), line(&self.tx)))
+    }
+
+    fn draw(&self, state: &State) -> Option<Block> {
+        let height = state.font.height();
+        let width = self.rx.len();
+        let mut pixels = vec![state.background; height * width];
+
+        let scale = self.scale();
+        let level = |rate: f32| (((rate / scale) * height as f32) as usize).min(height);
+        // The row of the transmitted rate in the previous column, for the line to connect to.
+        let mut previous: Option<usize> = None;
+        for (x, (&rx, &tx)) in self.rx.iter().zip(self.tx.iter()).enumerate() {
+            // The received rate is filled in from the bottom.
+            for y in height - level(rx)..height {
+                pixels[y * width + x] = state.graph_color;
+            }
+
+            // The transmitted rate is a line, which runs up or down to just short of the previous
+            // column. Its pixels are flipped where it overlaps with the received rate, so that it
+            // always shows. When nothing is transmitted, there is no line.
+            let point = (level(tx) > 0).then(|| height - level(tx));
+            let rows = match (point, previous) {
+                (Some(point), Some(previous)) if previous < point => previous + 1..point + 1,
+                (Some(point), Some(previous)) => point..previous.max(point + 1),
+                (Some(point), None) => point..point + 1,
+                (None, _) => 0..0,
+            };
+            for y in rows {
+                let idx = y * width + x;
+                pixels[idx] = if pixels[idx] == state.graph_color {
+                    state.background
+                } else {
+                    state.graph_color
+                };
+            }
+            previous = point;
+        }
+
+        Some(Block::new(height, pixels))
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use super::{required_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::state::{Block, State};
+
+/// Blank space of a fixed number of pixels.
+#[derive(Debug, Clone)]
+pub struct Padding(pub usize);
+
+impl Element for Padding {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Ok(Self(required_argument(argument)?.parse()?))
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        self.0
+    }
+
+    fn draw(&self, _state: &State) -> Option<Block> {
+        None
+    }
+}
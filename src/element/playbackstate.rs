@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+use super::{no_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// Asking mpd for its status is a round trip over the network.
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// The playback state of mpd.
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackState(pub mpd::State);
+
+fn symbol(state: mpd::State) -> &'static str {
+    match state {
+        mpd::State::Stop => "#",
+        mpd::State::Play => ">",
+        mpd::State::Pause => "\"",
+    }
+}
+
+impl Element for PlaybackState {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        no_argument(argument)?;
+        Ok(Self::default())
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        [mpd::State::Stop, mpd::State::Play, mpd::State::Pause]
+            .map(|state| font.determine_width(symbol(state)))
+            .into_iter()
+            .max()
+            .unwrap()
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(INTERVAL)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Music)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        // If we have access to mpd, and we get a state when we ask it, change to that.
+        if let Some(state) = sources
+            .music
+            .as_mut()
+            .and_then(|music| music.playback_state())
+        {
+            self.0 = state
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(symbol(self.0).to_string())
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use super::{no_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The widest rendering of the number of processes we make room for.
+const PROCS_TEMPLATE: &str = "00000";
+
+/// The number of processes.
+#[derive(Debug, Clone, Default)]
+pub struct Procs(pub usize);
+
+impl Element for Procs {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        no_argument(argument)?;
+        Ok(Self::default())
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(PROCS_TEMPLATE)
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        // Going through all processes takes a while, so we don't do it too often.
+        Some(Duration::from_secs(2))
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Processes)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.0 = sources.sys.process_count();
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.0 as f32)
+    }
+}
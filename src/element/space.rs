@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use super::{no_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::state::{Block, State};
+
+const SPACE: &str = "  ";
+
+/// Blank space the width of two spaces in the font.
+#[derive(Debug, Clone)]
+pub struct Space;
+
+impl Element for Space {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        no_argument(argument)?;
+        Ok(Self)
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(SPACE)
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(SPACE.to_string())
+    }
+
+    fn is_separator(&self) -> bool {
+        true
+    }
+
+    fn draw(&self, _state: &State) -> Option<Block> {
+        None
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use super::mem::MemoryView;
+use super::{Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The swap usage, as a percentage by default.
+#[derive(Debug, Clone, Default)]
+pub struct Swap {
+    pub view: MemoryView,
+    /// The used swap in bytes.
+    pub used: u64,
+    /// The total swap in bytes.
+    pub total: u64,
+}
+
+impl Element for Swap {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        use MemoryView::*;
+        Ok(Self {
+            view: MemoryView::parse(argument, &[Percentage, Absolute, Available])?,
+            ..Default::default()
+        })
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        self.view.width(font)
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        // The swap is refreshed along with the memory.
+        Some(SourceKind::Memory)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.used = sources.sys.used_swap();
+        self.total = sources.sys.total_swap();
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.view.format(self.used, self.total, None))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.view.value(self.used, self.total, None))
+    }
+
+    fn range(&self) -> Option<(f32, f32)> {
+        Some(self.view.range(self.total))
+    }
+}
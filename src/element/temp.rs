@@ -0,0 +1,107 @@
+use std::time::Instant;
+
+use super::{required_argument, Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+/// The unit in which a temperature is shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    fn symbol(&self) -> char {
+        match self {
+            TemperatureUnit::Celsius => 'C',
+            TemperatureUnit::Fahrenheit => 'F',
+        }
+    }
+
+    fn convert(&self, celsius: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
+/// The temperature of a sensor, such as ` 54C`.
+#[derive(Debug, Clone)]
+pub struct Temp {
+    /// (A part of) the label of the sensor, such as `Package id 0`.
+    pub label: String,
+    pub unit: TemperatureUnit,
+    /// The temperature in degrees Celsius, which is NaN if there is no such sensor.
+    pub celsius: f32,
+}
+
+impl Temp {
+    pub fn new(label: &str, unit: TemperatureUnit) -> Self {
+        Self {
+            label: label.to_string(),
+            unit,
+            celsius: f32::NAN,
+        }
+    }
+}
+
+/// Find the temperature of the sensor that is labeled `label`, or otherwise the first sensor with
+/// a label that contains it, regardless of case.
+fn find_temperature(temperatures: &[(String, f32)], label: &str) -> Option<f32> {
+    let label = label.to_lowercase();
+    let exact = temperatures
+        .iter()
+        .find(|(candidate, _)| candidate.to_lowercase() == label);
+    let partial = || {
+        temperatures
+            .iter()
+            .find(|(candidate, _)| candidate.to_lowercase().contains(&label))
+    };
+    exact.or_else(partial).map(|(_, celsius)| *celsius)
+}
+
+impl Element for Temp {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        let argument = required_argument(argument)?;
+        let (label, unit) = match argument.rsplit_once(',') {
+            Some((label, unit)) => {
+                let unit = match unit.trim() {
+                    "C" | "c" => TemperatureUnit::Celsius,
+                    "F" | "f" => TemperatureUnit::Fahrenheit,
+                    weird => return Err(ElementParseError::UnknownUnit(weird.to_string())),
+                };
+                (label, unit)
+            }
+            None => (argument, TemperatureUnit::default()),
+        };
+        Ok(Self::new(label.trim(), unit))
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        font.determine_width(&format!("000{}", self.unit.symbol()))
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::Sensors)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        let temperatures = sources.sensors.temperatures();
+        self.celsius = find_temperature(&temperatures, &self.label).unwrap_or(f32::NAN);
+    }
+
+    fn text(&self) -> Option<String> {
+        let symbol = self.unit.symbol();
+        if self.celsius.is_nan() {
+            return Some(format!("---{symbol}"));
+        }
+        Some(format!("{:>3.0}{symbol}", self.unit.convert(self.celsius)))
+    }
+
+    fn value(&self) -> Option<f32> {
+        Some(self.unit.convert(self.celsius))
+    }
+}
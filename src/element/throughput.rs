@@ -0,0 +1,34 @@
+use std::time::Instant;
+
+/// Turns a pair of running totals of bytes, such as those received and transmitted over a network
+/// interface, into rates.
+#[derive(Debug, Clone, Default)]
+pub struct Throughput {
+    last: Option<(Instant, (u64, u64))>,
+}
+
+impl Throughput {
+    /// Take in the `totals` at `now`, and return the rates in bytes per second since the previous
+    /// totals, if there were any.
+    pub fn update(&mut self, now: Instant, totals: Option<(u64, u64)>) -> Option<(f32, f32)> {
+        let Some(totals) = totals else {
+            self.last = None;
+            return None;
+        };
+        let (then, previous) = self.last.replace((now, totals))?;
+        let secs = now.saturating_duration_since(then).as_secs_f32();
+        if secs == 0.0 {
+            return None;
+        }
+
+        // The totals start over when a device is reset, which is not negative traffic.
+        let rate = |total: u64, previous: u64| total.saturating_sub(previous) as f32 / secs;
+        Some((rate(totals.0, previous.0), rate(totals.1, previous.1)))
+    }
+}
+
+/// The rates both ways together, such as for a graph of all traffic, or NaN while they are
+/// unknown.
+pub fn combined(rates: Option<(f32, f32)>) -> f32 {
+    rates.map_or(f32::NAN, |(a, b)| a + b)
+}
@@ -0,0 +1,126 @@
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use super::{Alignment, DateTime, Element, ElementParseError, TimeFormat};
+use crate::font::Font;
+use crate::source::Sources;
+
+/// What sets one kind of date or time element apart from another.
+pub trait TimeKind: std::fmt::Debug {
+    /// The format that is used when none is given.
+    const DEFAULT_FORMAT: &'static str;
+    /// Whether a time zone must be given.
+    const ZONE_REQUIRED: bool = false;
+}
+
+/// The kinds of date and time elements.
+pub mod kind {
+    use super::TimeKind;
+
+    #[derive(Debug, Clone)]
+    pub enum Date {}
+
+    impl TimeKind for Date {
+        const DEFAULT_FORMAT: &'static str = "%Y-%m-%d";
+    }
+
+    #[derive(Debug, Clone)]
+    pub enum Time {}
+
+    impl TimeKind for Time {
+        const DEFAULT_FORMAT: &'static str = "%H:%M:%S";
+    }
+
+    #[derive(Debug, Clone)]
+    pub enum DateAndTime {}
+
+    impl TimeKind for DateAndTime {
+        const DEFAULT_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S";
+    }
+
+    #[derive(Debug, Clone)]
+    pub enum Clock {}
+
+    impl TimeKind for Clock {
+        const DEFAULT_FORMAT: &'static str = "%H:%M:%S";
+        const ZONE_REQUIRED: bool = true;
+    }
+}
+
+/// The current date.
+pub type Date = TimeElement<kind::Date>;
+/// The current time.
+pub type Time = TimeElement<kind::Time>;
+/// The current date and time.
+pub type DateAndTime = TimeElement<kind::DateAndTime>;
+/// The current time in another time zone.
+///
+/// Unlike [`Time`], a zone is required, as in `clock(Asia/Tokyo, %H:%M)`.
+pub type Clock = TimeElement<kind::Clock>;
+
+/// The current date or time in a format, which is set apart by its kind `K`.
+#[derive(Debug, Clone)]
+pub struct TimeElement<K> {
+    pub now: DateTime,
+    pub format: TimeFormat,
+    kind: PhantomData<K>,
+}
+
+impl<K: TimeKind> TimeElement<K> {
+    /// Create the element with a `format`, which shows the time until it is first updated.
+    pub fn new(format: TimeFormat) -> Self {
+        Self {
+            now: Default::default(),
+            format,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<K: TimeKind> Default for TimeElement<K> {
+    fn default() -> Self {
+        Self::new(TimeFormat::new(K::DEFAULT_FORMAT).expect("the default format is valid"))
+    }
+}
+
+impl<K: TimeKind> Element for TimeElement<K> {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        let format = match argument {
+            Some(argument) => TimeFormat::parse(argument, K::DEFAULT_FORMAT)?,
+            None if K::ZONE_REQUIRED => return Err(ElementParseError::MissingArgument),
+            None => TimeFormat::new(K::DEFAULT_FORMAT)?,
+        };
+        if K::ZONE_REQUIRED && format.zone().is_none() {
+            return Err(ElementParseError::MissingZone);
+        }
+        Ok(Self::new(format))
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        self.format.width(font)
+    }
+
+    fn alignment(&self) -> Alignment {
+        Alignment::Left
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+
+    fn aligned(&self) -> bool {
+        true
+    }
+
+    fn set_locale(&mut self, locale: chrono::Locale) {
+        self.format.set_locale(locale)
+    }
+
+    fn update(&mut self, _sources: &mut Sources, _now: Instant) {
+        self.now = chrono::Local::now();
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.format.format(&self.now))
+    }
+}
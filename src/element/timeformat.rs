@@ -0,0 +1,272 @@
+use std::collections::BTreeSet;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Locale, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+use super::{DateTime, ElementParseError};
+use crate::font::Font;
+
+/// A strftime format for dates and times, such as `%H:%M` or `%a %d %b`, in local time or in a
+/// particular time zone.
+///
+/// Names of weekdays and months are in the POSIX locale, unless another one is set.
+#[derive(Debug, Clone)]
+pub struct TimeFormat {
+    format: String,
+    zone: Option<Tz>,
+    locale: Locale,
+    /// Every rendering that each item of the format can have, to find the widest one.
+    renderings: Vec<Vec<String>>,
+}
+
+impl TimeFormat {
+    /// Create a format for the local time.
+    pub fn new(format: &str) -> Result<Self, ElementParseError> {
+        Self::with_zone(format, None)
+    }
+
+    /// Create a format for the time in `zone`, or the local time if it is `None`.
+    pub fn with_zone(format: &str, zone: Option<Tz>) -> Result<Self, ElementParseError> {
+        let items: Vec<Item> = StrftimeItems::new(format).collect();
+        if items.is_empty() || items.contains(&Item::Error) {
+            return Err(ElementParseError::BadFormat(format.to_string()));
+        }
+
+        let locale = Locale::POSIX;
+        Ok(Self {
+            format: format.to_string(),
+            zone,
+            locale,
+            renderings: renderings(&items, zone, locale),
+        })
+    }
+
+    /// Parse the argument of a date or time element.
+    ///
+    /// This is either just a format, or a time zone that is optionally followed by a comma and a
+    /// format, as in `America/New_York` or `Asia/Tokyo, %H:%M`. The zone can be given a label to
+    /// show in front of the time, as in `tokyo=Asia/Tokyo`.
+    ///
+    /// A format may contain commas itself, as in `%a, %d %b`, so the part before the first comma
+    /// is only taken as a zone if it is one.
+    pub fn parse(argument: &str, default_format: &str) -> Result<Self, ElementParseError> {
+        let (zone, format) = match argument.split_once(',') {
+            Some((zone, format)) if !zone.contains('%') && parse_zone(zone).is_ok() => {
+                (zone.trim(), format.trim())
+            }
+            // Without any '%', it cannot be a useful format, so it must be a zone.
+            _ if !argument.contains('%') => (argument, default_format),
+            _ => return Self::new(argument),
+        };
+        let (label, zone) = parse_zone(zone)?;
+        match label {
+            // A '%' in the label should not be taken as part of the format.
+            Some(label) => Self::with_zone(
+                &format!("{} {format}", label.replace('%', "%%")),
+                Some(zone),
+            ),
+            None => Self::with_zone(format, Some(zone)),
+        }
+    }
+
+    /// The time zone, or `None` for the local time.
+    pub fn zone(&self) -> Option<Tz> {
+        self.zone
+    }
+
+    /// Render names of weekdays and months in `locale`.
+    pub fn set_locale(&mut self, locale: Locale) {
+        let items: Vec<Item> = StrftimeItems::new(&self.format).collect();
+        self.locale = locale;
+        self.renderings = renderings(&items, self.zone, locale);
+    }
+
+    /// The width of the widest possible rendering of the format.
+    ///
+    /// The widest rendering of each item is taken separately, such that the combination may never
+    /// actually occur, but nothing that does occur is wider.
+    pub fn width(&self, font: &Font) -> usize {
+        self.renderings
+            .iter()
+            .map(|renderings| {
+                renderings
+                    .iter()
+                    .map(|rendering| font.determine_width(rendering))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .sum()
+    }
+
+    pub fn format(&self, dt: &DateTime) -> String {
+        let (format, locale) = (&self.format, self.locale);
+        match self.zone {
+            Some(zone) => dt
+                .with_timezone(&zone)
+                .format_localized(format, locale)
+                .to_string(),
+            None => dt.format_localized(format, locale).to_string(),
+        }
+    }
+}
+
+/// Parse a time zone like `Asia/Tokyo`, with an optional label like in `tokyo=Asia/Tokyo`.
+fn parse_zone(zone: &str) -> Result<(Option<&str>, Tz), ElementParseError> {
+    let (label, name) = match zone.split_once('=') {
+        Some((label, name)) => (Some(label.trim()), name.trim()),
+        None => (None, zone.trim()),
+    };
+    let zone = name
+        .parse()
+        .map_err(|_| ElementParseError::UnknownZone(name.to_string()))?;
+    Ok((label, zone))
+}
+
+/// Every rendering that each of the `items` can have in `zone` and `locale`.
+fn renderings(items: &[Item], zone: Option<Tz>, locale: Locale) -> Vec<Vec<String>> {
+    let samples = samples();
+    items
+        .iter()
+        .map(|item| {
+            let renderings: BTreeSet<String> = samples
+                .iter()
+                .filter_map(|naive| render(naive, zone, locale, std::iter::once(item)))
+                .collect();
+            renderings.into_iter().collect()
+        })
+        .collect()
+}
+
+/// Render the `items` for the wall-clock time `naive` in `zone`, if that time exists there.
+fn render<'a>(
+    naive: &NaiveDateTime,
+    zone: Option<Tz>,
+    locale: Locale,
+    items: impl Iterator<Item = &'a Item<'a>> + Clone,
+) -> Option<String> {
+    let rendering = match zone {
+        Some(zone) => zone
+            .from_local_datetime(naive)
+            .earliest()?
+            .format_localized_with_items(items, locale)
+            .to_string(),
+        None => chrono::Local
+            .from_local_datetime(naive)
+            .earliest()?
+            .format_localized_with_items(items, locale)
+            .to_string(),
+    };
+    Some(rendering)
+}
+
+/// Moments that between them show every value that each item of a format can take.
+fn samples() -> Vec<NaiveDateTime> {
+    // Every day of a leap year, with every hour, minute and second spread over them.
+    let days = (0..366).map(|i| {
+        NaiveDate::from_yo_opt(2024, i + 1)
+            .and_then(|date| date.and_hms_opt(i % 24, i % 60, i * 7 % 60))
+            .expect("the sample is a valid date and time")
+    });
+    // The last day of a year covers the years themselves, as well as the 53rd week.
+    let years = (2000..2100).map(|year| {
+        NaiveDate::from_ymd_opt(year, 12, 31)
+            .and_then(|date| date.and_hms_opt(23, 59, 59))
+            .expect("the sample is a valid date and time")
+    });
+    days.chain(years).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::{kind, Element, Registry, TimeElement, TimeKind};
+    use crate::font::testing::{psf2_font, uf2_font};
+
+    fn datetime() -> DateTime {
+        chrono::Local
+            .with_ymd_and_hms(2023, 11, 14, 9, 5, 1)
+            .single()
+            .expect("the date is unambiguous")
+    }
+
+    #[test]
+    fn custom_time_formats() {
+        let font = psf2_font();
+        let glyph_width = font.determine_width(" ");
+        let format = |format| TimeFormat::new(format).expect("the format is valid");
+        fn showing<K: TimeKind + 'static>(format: TimeFormat) -> Box<dyn Element> {
+            let mut element = TimeElement::<K>::new(format);
+            element.now = datetime();
+            Box::new(element)
+        }
+        let elements: [(Box<dyn Element>, &str); 3] = [
+            (showing::<kind::Time>(format("%H:%M")), "09:05"),
+            (showing::<kind::Date>(format("%a %d %b")), "Tue 14 Nov"),
+            (showing::<kind::DateAndTime>(format("%V")), "46"),
+        ];
+        for (element, expected) in elements {
+            assert_eq!(element.text().as_deref(), Some(expected));
+            // With a fixed-width font, every rendering of these formats is equally wide.
+            assert_eq!(element.width(&font), expected.len() * glyph_width);
+        }
+
+        // The widest rendering is found for each part of a format.
+        let font = uf2_font();
+        let widest = ["Wednesday", "Thursday", "Saturday"]
+            .map(|day| font.determine_width(day))
+            .into_iter()
+            .max()
+            .unwrap();
+        assert_eq!(format("%A").width(&font), widest);
+        assert!(TimeFormat::new("%Q").is_err());
+    }
+
+    #[test]
+    fn time_zone_clocks() {
+        const DEFAULT_FORMAT: &str = "%H:%M:%S";
+        let now = chrono::Utc
+            .with_ymd_and_hms(2023, 11, 14, 9, 5, 1)
+            .single()
+            .expect("the date is unambiguous")
+            .with_timezone(&chrono::Local);
+        let cases = [
+            ("America/New_York", "04:05:01"),
+            ("Asia/Tokyo, %H:%M", "18:05"),
+            ("tokyo=Asia/Tokyo, %H:%M", "tokyo 18:05"),
+            ("Pacific/Kiritimati, %a %d", "Tue 14"),
+            ("UTC", "09:05:01"),
+            // Commas in a format without a zone are part of the format.
+            ("%a, %d %b", "Tue, 14 Nov"),
+            ("%H:%M, %S", "09:05, 01"),
+        ];
+        for (argument, expected) in cases {
+            let format =
+                TimeFormat::parse(argument, DEFAULT_FORMAT).expect("the argument is valid");
+            assert_eq!(format.format(&now), expected);
+        }
+
+        let registry = Registry::default();
+        assert!(registry.parse("clock(Asia/Tokyo)").is_ok());
+        assert!(registry.parse("clock(%H:%M)").is_err());
+        assert!(registry.parse("time(Mars/Olympus_Mons)").is_err());
+    }
+
+    #[test]
+    fn localized_time_formats() {
+        let mut format = TimeFormat::new("%a %d %b").expect("the format is valid");
+        format.set_locale(Locale::nl_NL);
+        assert_eq!(format.format(&datetime()), "di 14 nov");
+
+        // The width is that of the longest localized name.
+        let font = uf2_font();
+        let mut format = TimeFormat::new("%A").expect("the format is valid");
+        format.set_locale(Locale::nl_NL);
+        let widest = ["woensdag", "donderdag", "zaterdag"]
+            .map(|day| font.determine_width(day))
+            .into_iter()
+            .max()
+            .unwrap();
+        assert_eq!(format.width(&font), widest);
+    }
+}
@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use super::{Element, ElementParseError};
+use crate::font::Font;
+use crate::source::{SourceKind, Sources};
+
+const DEFAULT_FORMAT: &str = "%dd %H:%M";
+
+/// The number of days of uptime we make room for. Anything longer is shown as the most we make
+/// room for.
+const MAX_DAYS: u64 = 999;
+
+/// How long the system has been running, like `3d 04:12`.
+#[derive(Debug, Clone)]
+pub struct Uptime {
+    pub uptime: Duration,
+    format: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Unit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+}
+
+impl Unit {
+    fn secs(self) -> u64 {
+        match self {
+            Unit::Seconds => 1,
+            Unit::Minutes => 60,
+            Unit::Hours => 60 * 60,
+            Unit::Days => 24 * 60 * 60,
+        }
+    }
+
+    /// The most of this unit that is ever shown, when it is the largest unit in the format.
+    fn max(self) -> u64 {
+        (MAX_DAYS + 1) * Unit::Days.secs() / self.secs() - 1
+    }
+
+    /// How many of the unit make up the next larger unit, if there is one.
+    fn per_next(self) -> Option<u64> {
+        match self {
+            Unit::Seconds | Unit::Minutes => Some(60),
+            Unit::Hours => Some(24),
+            Unit::Days => None,
+        }
+    }
+}
+
+/// A piece of an uptime format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Unit(Unit),
+}
+
+/// Parse a format in which `%d`, `%H`, `%M` and `%S` stand for the days, hours, minutes and
+/// seconds, and `%%` for a '%'.
+fn parse_format(format: &str) -> Result<Vec<Part>, ElementParseError> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            text.push(ch);
+            continue;
+        }
+        let unit = match chars.next() {
+            Some('d') => Unit::Days,
+            Some('H') => Unit::Hours,
+            Some('M') => Unit::Minutes,
+            Some('S') => Unit::Seconds,
+            Some('%') => {
+                text.push('%');
+                continue;
+            }
+            _ => return Err(ElementParseError::BadFormat(format.to_string())),
+        };
+        if !text.is_empty() {
+            parts.push(Part::Text(std::mem::take(&mut text)));
+        }
+        parts.push(Part::Unit(unit));
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    // An empty format would leave nothing to show.
+    if parts.is_empty() {
+        return Err(ElementParseError::BadFormat(format.to_string()));
+    }
+    Ok(parts)
+}
+
+impl Uptime {
+    /// Create the element with a format like `%dd %H:%M`, in which `%d`, `%H`, `%M` and `%S`
+    /// stand for the days, hours, minutes and seconds.
+    ///
+    /// The largest unit in the format takes up everything above it, such that `%H:%M` shows `76:12`
+    /// after three days, four hours and twelve minutes.
+    pub fn new(format: &str) -> Result<Self, ElementParseError> {
+        Ok(Self {
+            uptime: Duration::ZERO,
+            format: parse_format(format)?,
+        })
+    }
+
+    /// Render the format, with `number` giving the text for each unit and whether it is the
+    /// largest one.
+    fn render(&self, number: impl Fn(Unit, bool) -> String) -> String {
+        let largest = self
+            .format
+            .iter()
+            .filter_map(|part| match part {
+                Part::Unit(unit) => Some(*unit),
+                Part::Text(_) => None,
+            })
+            .max();
+        self.format
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Unit(unit) => number(*unit, Some(*unit) == largest),
+            })
+            .collect()
+    }
+}
+
+impl Default for Uptime {
+    fn default() -> Self {
+        Self::new(DEFAULT_FORMAT).expect("the default format is valid")
+    }
+}
+
+impl Element for Uptime {
+    fn parse(argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Self::new(argument.unwrap_or(DEFAULT_FORMAT))
+    }
+
+    fn width(&self, font: &Font) -> usize {
+        // The largest unit takes up everything above it, so it gets as many digits as its most.
+        let template = |unit: Unit, largest| {
+            if largest {
+                "0".repeat(unit.max().to_string().len())
+            } else {
+                "00".to_string()
+            }
+        };
+        font.determine_width(&self.render(template))
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
+    }
+
+    fn source(&self) -> Option<SourceKind> {
+        Some(SourceKind::System)
+    }
+
+    fn update(&mut self, sources: &mut Sources, _now: Instant) {
+        self.uptime = sources.sys.uptime();
+    }
+
+    fn text(&self) -> Option<String> {
+        let secs = self.uptime.as_secs().min(Unit::Seconds.max());
+        Some(self.render(|unit, largest| {
+            let amount = secs / unit.secs();
+            match unit.per_next() {
+                Some(per_next) if !largest => format!("{:02}", amount % per_next),
+                _ => amount.to_string(),
+            }
+        }))
+    }
+}
@@ -125,3 +125,37 @@ impl Iterator for Row<'_> {
 }
 
 impl ExactSizeIterator for Row<'_> {}
+
+/// Fonts for tests.
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::path::PathBuf;
+
+    use super::{load_font, Font};
+
+    /// The bundled geneva font, which is proportional.
+    pub fn uf2_font() -> Font {
+        let path = PathBuf::from_iter([env!("CARGO_MANIFEST_DIR"), "fonts", "geneva12.uf2"]);
+        load_font(&path).expect("the bundled font can be loaded")
+    }
+
+    /// Build a small psf2 font with a deterministic, if illegible, glyph for each ascii character.
+    pub fn psf2_font() -> Font {
+        const LENGTH: u32 = 128;
+        const WIDTH: u32 = 6;
+        const HEIGHT: u32 = 10;
+        let header = [0x864ab572, 0, 32, 0, LENGTH, HEIGHT, HEIGHT, WIDTH];
+        let mut data: Vec<u8> = header.iter().flat_map(|v| v.to_le_bytes()).collect();
+        for ch in 0..LENGTH as u8 {
+            for y in 0..HEIGHT as u8 {
+                let row = match ch {
+                    b' ' => 0,
+                    _ => ch.rotate_left(y as u32) ^ y.wrapping_mul(0x11),
+                };
+                // Only the leftmost WIDTH bits of each row are part of the glyph.
+                data.push(row & 0xfc);
+            }
+        }
+        Font::Psf2(psf2::Font::new(data).expect("the generated font is valid"))
+    }
+}
@@ -0,0 +1,98 @@
+use pixels::Pixels;
+
+use crate::config::{Pixel, PIXEL_SIZE};
+
+/// A target that the bar can be drawn onto.
+///
+/// A framebuffer is a rectangle of rgba pixels, stored row by row. The window backend is one
+/// implementation, but anything that can hand out such a buffer will do.
+pub trait Framebuffer {
+    /// Returns the width and height of the framebuffer in pixels.
+    fn size(&self) -> (usize, usize);
+
+    fn frame(&self) -> &[u8];
+
+    fn frame_mut(&mut self) -> &mut [u8];
+
+    /// Set every pixel in the framebuffer to `color`.
+    fn clear(&mut self, color: Pixel) {
+        self.frame_mut()
+            .array_chunks_mut()
+            .for_each(|px| *px = color);
+    }
+
+    /// Copy a row of pixels into the framebuffer, starting at (`x`, `y`).
+    fn put_row(&mut self, x: usize, y: usize, row: &[Pixel]) {
+        let (width, _) = self.size();
+        // Whatever sticks out on the right is cut off, rather than spilling onto the next row.
+        let row = &row[..row.len().min(width.saturating_sub(x))];
+        if row.is_empty() {
+            return;
+        }
+        let idx = (y * width + x) * PIXEL_SIZE;
+        let row_bytes = row.flatten();
+        self.frame_mut()[idx..idx + row_bytes.len()].copy_from_slice(row_bytes);
+    }
+}
+
+impl Framebuffer for Pixels {
+    fn size(&self) -> (usize, usize) {
+        let size = self.texture().size();
+        (size.width as usize, size.height as usize)
+    }
+
+    fn frame(&self) -> &[u8] {
+        self.frame()
+    }
+
+    fn frame_mut(&mut self) -> &mut [u8] {
+        self.frame_mut()
+    }
+}
+
+/// An in-memory framebuffer that is not tied to any window or gpu.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    frame: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, color: Pixel) -> Self {
+        Self {
+            width,
+            height,
+            frame: color.repeat(width * height),
+        }
+    }
+
+    /// Returns a copy of the canvas where each pixel is `factor` by `factor` pixels in size.
+    pub fn scaled(&self, factor: usize) -> Self {
+        let mut scaled = Self::new(self.width * factor, self.height * factor, [0; PIXEL_SIZE]);
+        for (y, row) in self.frame.chunks_exact(self.width * PIXEL_SIZE).enumerate() {
+            let scaled_row: Vec<Pixel> = row
+                .array_chunks::<PIXEL_SIZE>()
+                .flat_map(|&px| std::iter::repeat_n(px, factor))
+                .collect();
+            for sy in 0..factor {
+                scaled.put_row(0, y * factor + sy, &scaled_row);
+            }
+        }
+        scaled
+    }
+}
+
+impl Framebuffer for Canvas {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn frame(&self) -> &[u8] {
+        &self.frame
+    }
+
+    fn frame_mut(&mut self) -> &mut [u8] {
+        &mut self.frame
+    }
+}
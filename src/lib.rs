@@ -0,0 +1,26 @@
+//! A tiny clock and system information bar.
+//!
+//! The bar is made up of a list of [`Element`]s, which are kept up to date and drawn by a
+//! [`State`]. Drawing happens onto any [`Framebuffer`](framebuffer::Framebuffer), so the
+//! elements can be rendered into a window, an image, a terminal, or something else entirely.
+
+#![feature(array_chunks, slice_flatten)]
+
+pub mod app;
+pub mod config;
+pub mod element;
+pub mod font;
+pub mod framebuffer;
+pub mod output;
+pub mod render;
+pub mod schedule;
+pub mod source;
+pub mod state;
+pub mod surface;
+pub mod terminal;
+pub mod window;
+
+pub use config::Config;
+pub use element::Element;
+pub use font::{load_font, Font};
+pub use state::{History, State};
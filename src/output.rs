@@ -0,0 +1,206 @@
+use std::io::Write;
+use std::time::Instant;
+
+use crate::element::Element;
+use crate::framebuffer::{Canvas, Framebuffer};
+use crate::state::State;
+
+/// The header of the i3bar protocol, followed by the start of the endless array of status lines.
+const I3BAR_HEADER: &str = "{\"version\":1}\n[\n";
+
+/// Update the state and call `f` with it whenever elements are due, over and over again.
+fn run_updates(
+    state: &mut State,
+    mut f: impl FnMut(&State) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    loop {
+        if state.update_due(Instant::now(), &chrono::Local::now()) {
+            f(state)?;
+        }
+        match state.next_update() {
+            Some(deadline) => {
+                std::thread::sleep(deadline.saturating_duration_since(Instant::now()))
+            }
+            // Nothing will ever change anymore.
+            None => std::thread::park(),
+        }
+    }
+}
+
+/// Write the state as a status line in the i3bar JSON protocol to stdout, every time it is updated.
+///
+/// See <https://i3wm.org/docs/i3bar-protocol.html> for the protocol. It is also understood by
+/// swaybar.
+pub fn run_i3bar(state: &mut State) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "{I3BAR_HEADER}")?;
+    run_updates(state, |state| {
+        writeln!(stdout, "{},", i3bar_blocks(state.elements(), state.names()))?;
+        stdout.flush()
+    })
+}
+
+/// Write the state as a line of plain text to stdout, every time it is updated.
+///
+/// When `in_place` is set, each line overwrites the previous one, rather than being printed
+/// below it. Otherwise, the output is suitable for `tmux` status lines and the like.
+pub fn run_text(state: &mut State, in_place: bool) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    run_updates(state, |state| {
+        let line = text_line(state.elements());
+        if in_place {
+            // Return to the start of the line, and clear whatever remains of the previous one.
+            write!(stdout, "\r{line}\x1b[K")?;
+        } else {
+            writeln!(stdout, "{line}")?;
+        }
+        stdout.flush()
+    })
+}
+
+/// Draw the state as an image in the terminal, every time it is updated.
+///
+/// The screen is cleared once, and after that every new image is drawn over the previous one in
+/// the top left corner. Each logical pixel is drawn as `scale_factor` by `scale_factor` pixels.
+pub fn run_graphics(
+    state: &mut State,
+    encode: fn(&dyn Framebuffer) -> String,
+    scale_factor: usize,
+) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "\x1b[2J")?;
+    run_updates(state, |state| {
+        let (width, height) = state.window_size();
+        let mut canvas = Canvas::new(width as usize, height as usize, state.background);
+        state.draw(&mut canvas);
+        let image = encode(&canvas.scaled(scale_factor));
+        // Move the cursor home before drawing.
+        write!(stdout, "\x1b[H{image}")?;
+        stdout.flush()
+    })
+}
+
+/// Format the elements as a single line of text.
+///
+/// The values are formatted just like they are drawn in the window. Elements without text, such
+/// as padding, are dropped.
+fn text_line(elements: &[Box<dyn Element>]) -> String {
+    elements
+        .iter()
+        .map(|element| element.text().unwrap_or_default())
+        .collect()
+}
+
+/// Format the elements as a JSON array of i3bar blocks.
+///
+/// Elements without text are dropped, and a separator such as a space is turned into a separator
+/// after the preceding block. Blocks are named after the elements, where they have a name.
+fn i3bar_blocks(elements: &[Box<dyn Element>], names: &[Option<&str>]) -> String {
+    let mut blocks: Vec<(Option<&str>, String, bool)> = Vec::new();
+    for (element, &name) in elements.iter().zip(names) {
+        if element.is_separator() {
+            if let Some((_, _, separator)) = blocks.last_mut() {
+                *separator = true;
+            }
+            continue;
+        }
+        let Some(full_text) = element.text() else {
+            continue;
+        };
+        blocks.push((name, full_text, false));
+    }
+
+    let blocks: Vec<String> = blocks
+        .into_iter()
+        .map(|(name, full_text, separator)| {
+            let name = name
+                .map(|name| format!("\"name\":{},", json_string(name)))
+                .unwrap_or_default();
+            format!(
+                "{{{name}\"full_text\":{},\"separator\":{separator}}}",
+                json_string(&full_text),
+            )
+        })
+        .collect();
+    format!("[{}]", blocks.join(","))
+}
+
+/// Quote and escape a string for use in JSON.
+fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if ch.is_control() => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::{Label, Padding, Registry, Space};
+
+    #[test]
+    fn i3bar_json() {
+        assert_eq!(I3BAR_HEADER, "{\"version\":1}\n[\n");
+
+        let elements: Vec<Box<dyn Element>> = vec![
+            Box::new(Label("a".to_string())),
+            Box::new(Space),
+            Box::new(Padding(4)),
+            Box::new(Label("b".to_string())),
+        ];
+        assert_eq!(
+            i3bar_blocks(&elements, &[None; 4]),
+            "[{\"full_text\":\"a\",\"separator\":true},\
+            {\"full_text\":\"b\",\"separator\":false}]"
+        );
+        assert_eq!(i3bar_blocks(&[], &[]), "[]");
+
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(json_string("a\\b"), "\"a\\\\b\"");
+        assert_eq!(json_string("\n\r\t"), "\"\\n\\r\\t\"");
+        assert_eq!(json_string("\x1b\x7f"), "\"\\u001b\\u007f\"");
+        assert_eq!(json_string("▁█°"), "\"▁█°\"");
+    }
+
+    #[test]
+    fn i3bar_names() {
+        let registry = Registry::default();
+        let (elements, names): (Vec<_>, Vec<_>) = ["label(x)", "space", "padding(2)"]
+            .map(|spec| registry.parse(spec).expect("the spec is valid"))
+            .into_iter()
+            .map(|scheduled| (scheduled.element, scheduled.name))
+            .chain([(Box::new(Label("y".to_string())) as Box<dyn Element>, None)])
+            .unzip();
+        assert_eq!(
+            i3bar_blocks(&elements, &names),
+            "[{\"name\":\"label\",\"full_text\":\"x\",\"separator\":true},\
+            {\"full_text\":\"y\",\"separator\":false}]"
+        );
+    }
+
+    #[test]
+    fn text_lines() {
+        let elements: Vec<Box<dyn Element>> = vec![
+            Box::new(Label("a".to_string())),
+            Box::new(Space),
+            Box::new(Padding(4)),
+            Box::new(Label("b".to_string())),
+            Box::new(Padding(4)),
+        ];
+        // Padding is dropped, and a space stays a space.
+        assert_eq!(text_line(&elements), "a  b");
+        assert_eq!(text_line(&[]), "");
+    }
+}
@@ -0,0 +1,34 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::framebuffer::{Canvas, Framebuffer};
+use crate::state::State;
+
+/// Update the state once and write the resulting bar to a png file at `path`.
+///
+/// This does not need a window or a gpu, which makes it suitable for taking screenshots and for
+/// running on machines without a display.
+pub fn render_png(state: &mut State, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let (width, height) = state.update_and_size();
+    let mut canvas = Canvas::new(width as usize, height as usize, state.background);
+    state.draw(&mut canvas);
+    write_png(&canvas, path)
+}
+
+/// Write the contents of a framebuffer to a png file at `path`.
+pub fn write_png(
+    framebuffer: &dyn Framebuffer,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (width, height) = framebuffer.size();
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()?
+        .write_image_data(framebuffer.frame())?;
+
+    Ok(())
+}
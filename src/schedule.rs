@@ -0,0 +1,232 @@
+//! Deciding when each element is updated.
+
+use std::time::{Duration, Instant};
+
+use crate::element::{DateTime, Element, ElementParseError};
+use crate::source::SourceKind;
+
+/// An element together with the interval at which it is updated.
+///
+/// An interval of `None` means the element is only updated once.
+#[derive(Debug)]
+pub struct Scheduled {
+    pub element: Box<dyn Element>,
+    pub interval: Option<Duration>,
+    /// The name of the element in the elements specification, if it came from one.
+    pub name: Option<&'static str>,
+}
+
+impl From<Box<dyn Element>> for Scheduled {
+    /// Schedule the element at its own preferred interval.
+    fn from(element: Box<dyn Element>) -> Self {
+        let interval = element.interval();
+        Self {
+            element,
+            interval,
+            name: None,
+        }
+    }
+}
+
+/// The shortest interval of the elements that read from `source`, if there are any.
+///
+/// This is how often the source needs to be polled to keep all of them up to date.
+pub fn source_interval(elements: &[Scheduled], source: SourceKind) -> Option<Duration> {
+    elements
+        .iter()
+        .filter(|scheduled| scheduled.element.source() == Some(source))
+        .filter_map(|scheduled| scheduled.interval)
+        .min()
+}
+
+/// How long after a wall-clock boundary an aligned element is updated, to be sure that we don't
+/// wake up just before it.
+const ALIGNMENT_MARGIN: Duration = Duration::from_millis(1);
+
+/// The longest an aligned element goes without an update, whatever its interval.
+///
+/// We sleep on the monotonic clock, which knows nothing of the wall clock being set or of the
+/// time spent in suspend. Looking at the wall clock this often keeps the shown time from going
+/// stale for longer than this when either happens.
+const MAX_ALIGNED_SLEEP: Duration = Duration::from_secs(1);
+
+/// Keeps track of when each element is due for its next update.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    intervals: Vec<Option<Duration>>,
+    aligned: Vec<bool>,
+    deadlines: Vec<Deadline>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Deadline {
+    /// Not updated yet, so due right away.
+    Now,
+    At(Instant),
+    /// Will not be updated again.
+    Never,
+}
+
+impl Schedule {
+    /// Create a schedule for `elements` in which every element is due right away.
+    pub fn new(elements: &[Scheduled]) -> Self {
+        Self {
+            intervals: elements
+                .iter()
+                .map(|scheduled| scheduled.interval)
+                .collect(),
+            aligned: elements
+                .iter()
+                .map(|scheduled| scheduled.element.aligned())
+                .collect(),
+            deadlines: vec![Deadline::Now; elements.len()],
+        }
+    }
+
+    /// Whether the element at `index` is due for an update at `now`.
+    pub fn is_due(&self, index: usize, now: Instant) -> bool {
+        match self.deadlines[index] {
+            Deadline::Now => true,
+            Deadline::At(deadline) => deadline <= now,
+            Deadline::Never => false,
+        }
+    }
+
+    /// Record that the element at `index` was updated at `now`, when the wall clock read `wall`,
+    /// and set its next deadline.
+    pub fn updated(&mut self, index: usize, now: Instant, wall: &DateTime) {
+        let Some(interval) = self.intervals[index] else {
+            self.deadlines[index] = Deadline::Never;
+            return;
+        };
+        if self.aligned[index] {
+            // We sleep on the monotonic clock, but work out how long to sleep from the wall clock
+            // every time. That way, jumps of the system clock and changes to daylight saving time
+            // show up on the next tick, or sooner for long intervals.
+            let sleep = until_aligned(wall, interval).min(MAX_ALIGNED_SLEEP);
+            self.deadlines[index] = Deadline::At(now + sleep);
+            return;
+        }
+
+        // Keep to the rhythm of the previous deadlines, so that the updates don't drift. If we have
+        // fallen behind by more than an interval, there is no point in catching up.
+        let next = match self.deadlines[index] {
+            Deadline::At(deadline) if deadline + interval > now => deadline + interval,
+            _ => now + interval,
+        };
+        self.deadlines[index] = Deadline::At(next);
+    }
+
+    /// The earliest moment at which any element is due, if there is one.
+    ///
+    /// Elements that have not been updated yet are due at `now`.
+    pub fn next_deadline(&self, now: Instant) -> Option<Instant> {
+        self.deadlines
+            .iter()
+            .filter_map(|deadline| match deadline {
+                Deadline::Now => Some(now),
+                Deadline::At(deadline) => Some(*deadline),
+                Deadline::Never => None,
+            })
+            .min()
+    }
+}
+
+/// How long it is from `wall` until just after the next whole multiple of `interval` in local
+/// time, such as the next second or the next minute.
+fn until_aligned(wall: &DateTime, interval: Duration) -> Duration {
+    const NANOS_PER_SEC: i128 = 1_000_000_000;
+    let local = wall.naive_local();
+    let since_epoch =
+        i128::from(local.timestamp()) * NANOS_PER_SEC + i128::from(local.timestamp_subsec_nanos());
+    let interval = interval.as_nanos() as i128;
+    let remaining = interval - since_epoch.rem_euclid(interval);
+    Duration::from_nanos(remaining as u64) + ALIGNMENT_MARGIN
+}
+
+/// Parse an interval like `250ms`, `30s`, `5m` or `1h`.
+pub fn parse_interval(s: &str) -> Result<Duration, ElementParseError> {
+    let bad_interval = || ElementParseError::BadInterval(s.to_string());
+    let split = s
+        .find(|ch: char| !ch.is_ascii_digit())
+        .ok_or_else(bad_interval)?;
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| bad_interval())?;
+    let interval = match unit {
+        "ms" => Duration::from_millis(amount),
+        "s" => Duration::from_secs(amount),
+        "m" => Duration::from_secs(amount)
+            .checked_mul(60)
+            .ok_or_else(bad_interval)?,
+        "h" => Duration::from_secs(amount)
+            .checked_mul(60 * 60)
+            .ok_or_else(bad_interval)?,
+        _ => return Err(bad_interval()),
+    };
+    if interval.is_zero() {
+        return Err(bad_interval());
+    }
+    Ok(interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::element::Time;
+
+    fn datetime() -> DateTime {
+        chrono::Local
+            .with_ymd_and_hms(2023, 11, 14, 9, 5, 1)
+            .single()
+            .expect("the date is unambiguous")
+    }
+
+    #[test]
+    fn aligned_to_wall_clock() {
+        let wall = datetime() + chrono::Duration::milliseconds(250);
+        let after = |duration: Duration| duration + Duration::from_millis(1);
+        assert_eq!(
+            until_aligned(&wall, Duration::from_secs(1)),
+            after(Duration::from_millis(750))
+        );
+        assert_eq!(
+            until_aligned(&wall, Duration::from_secs(60)),
+            after(Duration::from_millis(58_750))
+        );
+
+        // Right on a boundary, the next one is a whole interval away.
+        assert_eq!(
+            until_aligned(&datetime(), Duration::from_secs(1)),
+            after(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn aligned_sleep_is_capped() {
+        let elements = [Scheduled {
+            element: Box::new(Time::default()),
+            interval: Some(Duration::from_secs(60)),
+            name: None,
+        }];
+        let mut schedule = Schedule::new(&elements);
+        let now = Instant::now();
+
+        // A minute boundary that is far off is not waited for in one go, in case the wall clock
+        // changes in the meantime.
+        schedule.updated(0, now, &(datetime() + chrono::Duration::seconds(10)));
+        assert_eq!(schedule.next_deadline(now), Some(now + MAX_ALIGNED_SLEEP));
+
+        // One that is close is.
+        schedule.updated(
+            0,
+            now,
+            &(datetime() + chrono::Duration::milliseconds(58_500)),
+        );
+        assert_eq!(
+            schedule.next_deadline(now),
+            Some(now + Duration::from_millis(501))
+        );
+    }
+}
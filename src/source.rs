@@ -0,0 +1,348 @@
+use std::path::Path;
+use std::time::Duration;
+
+use battery::Battery;
+use sysinfo::{
+    ComponentExt, CpuExt, CpuRefreshKind, DiskExt, NetworkExt, NetworksExt, ProcessRefreshKind,
+    System, SystemExt,
+};
+
+pub mod background;
+pub mod fake;
+pub mod sysfs;
+
+/// The kinds of data sources, so that elements can say which one they read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    /// The system as a whole, such as its load and uptime, which is always up to date.
+    System,
+    Cpu,
+    Memory,
+    Processes,
+    Network,
+    Disk,
+    Sensors,
+    Frequency,
+    Battery,
+    Music,
+}
+
+/// Everything the elements get their data from.
+pub struct Sources {
+    pub sys: Box<dyn SystemSource>,
+    pub sensors: Box<dyn SensorSource>,
+    pub frequency: Box<dyn FrequencySource>,
+    pub battery: Option<Box<dyn BatterySource>>,
+    pub music: Option<Box<dyn MusicSource>>,
+}
+
+/// A source of information about the cpus, memory, network interfaces and disks.
+pub trait SystemSource {
+    fn refresh_cpu(&mut self);
+
+    fn refresh_memory(&mut self);
+
+    fn refresh_processes(&mut self);
+
+    /// Refresh the numbers of the network interfaces that are known.
+    fn refresh_networks(&mut self);
+
+    /// Look for the network interfaces again, and refresh their numbers.
+    fn refresh_networks_list(&mut self);
+
+    /// Refresh the numbers of the disks that are known.
+    fn refresh_disks(&mut self);
+
+    /// Look for the mounted disks again, and refresh their numbers.
+    fn refresh_disks_list(&mut self);
+
+    /// Returns the usage of each cpu as a percentage.
+    fn cpu_usages(&self) -> Vec<f32>;
+
+    /// Returns the amount of used memory in bytes, which is everything that is not available for
+    /// new programs.
+    fn used_memory(&self) -> u64;
+
+    /// Returns the total amount of memory in bytes.
+    fn total_memory(&self) -> u64;
+
+    /// Returns the amount of memory in bytes that is used for buffers and for the page cache, if
+    /// that is known.
+    fn buffers_and_cache(&self) -> Option<(u64, u64)>;
+
+    /// Returns the amount of used swap in bytes.
+    fn used_swap(&self) -> u64;
+
+    /// Returns the total amount of swap in bytes.
+    fn total_swap(&self) -> u64;
+
+    /// Returns the load averages over the last 1, 5 and 15 minutes.
+    fn load_average(&self) -> [f64; 3];
+
+    /// Returns how long the system has been running.
+    fn uptime(&self) -> Duration;
+
+    /// Returns the number of processes.
+    fn process_count(&self) -> usize;
+
+    /// Returns the number of bytes received and transmitted over `interface` so far, or `None` if
+    /// there is no such interface.
+    fn network_totals(&self, interface: &str) -> Option<(u64, u64)>;
+
+    /// Returns the used and total space in bytes of the disk mounted at `mount_point`, or `None`
+    /// if nothing is mounted there.
+    fn disk_usage(&self, mount_point: &str) -> Option<(u64, u64)>;
+
+    /// Returns the number of bytes read from and written to the block device `device`, such as
+    /// `sda`, so far, or `None` if there is no such device.
+    fn disk_io_totals(&self, device: &str) -> Option<(u64, u64)>;
+}
+
+impl SystemSource for System {
+    fn refresh_cpu(&mut self) {
+        SystemExt::refresh_cpu(self)
+    }
+
+    fn refresh_memory(&mut self) {
+        SystemExt::refresh_memory(self)
+    }
+
+    fn refresh_processes(&mut self) {
+        // We only count them, so there is no need to look into each of them.
+        self.refresh_processes_specifics(ProcessRefreshKind::new())
+    }
+
+    fn refresh_networks(&mut self) {
+        SystemExt::refresh_networks(self)
+    }
+
+    fn refresh_networks_list(&mut self) {
+        SystemExt::refresh_networks_list(self)
+    }
+
+    fn refresh_disks(&mut self) {
+        SystemExt::refresh_disks(self)
+    }
+
+    fn refresh_disks_list(&mut self) {
+        SystemExt::refresh_disks_list(self)
+    }
+
+    fn cpu_usages(&self) -> Vec<f32> {
+        self.cpus().iter().map(|cpu| cpu.cpu_usage()).collect()
+    }
+
+    fn used_memory(&self) -> u64 {
+        SystemExt::used_memory(self)
+    }
+
+    fn total_memory(&self) -> u64 {
+        SystemExt::total_memory(self)
+    }
+
+    fn buffers_and_cache(&self) -> Option<(u64, u64)> {
+        // sysinfo does not tell these apart from the rest, so we ask the kernel directly.
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let buffers = parse_meminfo(&meminfo, "Buffers")?;
+        let cached = parse_meminfo(&meminfo, "Cached")?;
+        Some((buffers, cached))
+    }
+
+    fn used_swap(&self) -> u64 {
+        SystemExt::used_swap(self)
+    }
+
+    fn total_swap(&self) -> u64 {
+        SystemExt::total_swap(self)
+    }
+
+    fn load_average(&self) -> [f64; 3] {
+        let load = SystemExt::load_average(self);
+        [load.one, load.five, load.fifteen]
+    }
+
+    fn uptime(&self) -> Duration {
+        Duration::from_secs(SystemExt::uptime(self))
+    }
+
+    fn process_count(&self) -> usize {
+        self.processes().len()
+    }
+
+    fn network_totals(&self, interface: &str) -> Option<(u64, u64)> {
+        self.networks()
+            .iter()
+            .find(|(name, _)| *name == interface)
+            .map(|(_, data)| (data.total_received(), data.total_transmitted()))
+    }
+
+    fn disk_usage(&self, mount_point: &str) -> Option<(u64, u64)> {
+        self.disks()
+            .iter()
+            .find(|disk| disk.mount_point() == Path::new(mount_point))
+            .map(|disk| {
+                let total = disk.total_space();
+                (total.saturating_sub(disk.available_space()), total)
+            })
+    }
+
+    fn disk_io_totals(&self, device: &str) -> Option<(u64, u64)> {
+        // sysinfo only counts the disk usage of each process, so we ask the kernel directly.
+        let stats = std::fs::read_to_string("/proc/diskstats").ok()?;
+        parse_diskstats(&stats, device)
+    }
+}
+
+/// The size of a sector as counted in `/proc/diskstats`, whatever the actual sector size of the
+/// device is.
+const DISKSTATS_SECTOR_SIZE: u64 = 512;
+
+/// Find the number of bytes read from and written to `device` in the contents of
+/// `/proc/diskstats`.
+fn parse_diskstats(stats: &str, device: &str) -> Option<(u64, u64)> {
+    stats.lines().find_map(|line| {
+        // The fields are the major and minor numbers, the name, and then the statistics, of which
+        // the third and seventh are the sectors read and written.
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(2) != Some(&device) {
+            return None;
+        }
+        let sectors = |i: usize| fields.get(i)?.parse::<u64>().ok();
+        Some((
+            sectors(5)? * DISKSTATS_SECTOR_SIZE,
+            sectors(9)? * DISKSTATS_SECTOR_SIZE,
+        ))
+    })
+}
+
+/// Find the amount of bytes that `key`, such as `Buffers`, is set to in the contents of
+/// `/proc/meminfo`.
+fn parse_meminfo(meminfo: &str, key: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        // Lines look like `Buffers:          123456 kB`.
+        let (name, value) = line.split_once(':')?;
+        if name != key {
+            return None;
+        }
+        let kibibytes: u64 = value.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some(kibibytes * 1024)
+    })
+}
+
+/// A source of temperatures.
+pub trait SensorSource {
+    fn refresh(&mut self);
+
+    /// Returns the label and the temperature in degrees Celsius of each sensor.
+    fn temperatures(&self) -> Vec<(String, f32)>;
+}
+
+impl SensorSource for System {
+    fn refresh(&mut self) {
+        // The sensors are looked for once, and only read after that.
+        if self.components().is_empty() {
+            self.refresh_components_list()
+        } else {
+            self.refresh_components()
+        }
+    }
+
+    fn temperatures(&self) -> Vec<(String, f32)> {
+        self.components()
+            .iter()
+            .map(|component| (component.label().to_string(), component.temperature()))
+            .collect()
+    }
+}
+
+/// A source of the frequencies of the cpus.
+pub trait FrequencySource {
+    fn refresh(&mut self);
+
+    /// Returns the current frequency of each cpu in MHz.
+    fn frequencies(&self) -> Vec<u64>;
+
+    /// Returns the name of the governor that scales the frequencies, such as `powersave`, if it is
+    /// known.
+    fn governor(&self) -> Option<String>;
+}
+
+impl FrequencySource for System {
+    fn refresh(&mut self) {
+        self.refresh_cpu_specifics(CpuRefreshKind::new().with_frequency())
+    }
+
+    fn frequencies(&self) -> Vec<u64> {
+        self.cpus().iter().map(|cpu| cpu.frequency()).collect()
+    }
+
+    fn governor(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A source of battery information.
+pub trait BatterySource {
+    fn refresh(&mut self);
+
+    /// Returns the state of charge as a percentage.
+    fn state_of_charge(&self) -> f32;
+}
+
+impl BatterySource for Battery {
+    fn refresh(&mut self) {
+        // A battery derefs to its platform device, which knows how to refresh itself.
+        let _ = (**self).refresh();
+    }
+
+    fn state_of_charge(&self) -> f32 {
+        Battery::state_of_charge(self).get::<battery::units::ratio::percent>()
+    }
+}
+
+/// A source of music playback information.
+pub trait MusicSource {
+    /// Ask for the current playback state, if it can be determined.
+    fn playback_state(&mut self) -> Option<mpd::State>;
+}
+
+impl MusicSource for mpd::Client {
+    fn playback_state(&mut self) -> Option<mpd::State> {
+        self.status().ok().map(|status| status.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_diskstats() {
+        let stats = "\
+   8       0 sda 1034 27 94278 510 288 197 5602 1311 0 1008 1821 0 0 0 0 0 0
+   8       1 sda1 980 27 90190 496 288 197 5602 1311 0 988 1807 0 0 0 0 0 0
+ 259       0 nvme0n1 52011 1604 4096118 6521 91920 86044 6093688 60112 0 70328 76050";
+        assert_eq!(
+            parse_diskstats(stats, "sda"),
+            Some((94278 * 512, 5602 * 512))
+        );
+        assert_eq!(
+            parse_diskstats(stats, "nvme0n1"),
+            Some((4096118 * 512, 6093688 * 512))
+        );
+        assert_eq!(parse_diskstats(stats, "sdb"), None);
+    }
+
+    #[test]
+    fn read_meminfo() {
+        let meminfo = "\
+MemTotal:       16303448 kB
+MemFree:         1232760 kB
+Buffers:          319488 kB
+Cached:          4297728 kB
+SwapCached:         2048 kB";
+        assert_eq!(parse_meminfo(meminfo, "Buffers"), Some(319488 * 1024));
+        assert_eq!(parse_meminfo(meminfo, "Cached"), Some(4297728 * 1024));
+        assert_eq!(parse_meminfo(meminfo, "Shmem"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Data sources that are polled on a worker thread, so that reading them never blocks.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{BatterySource, MusicSource};
+
+/// The latest value of a source that is polled on its own thread.
+///
+/// The worker thread stops once the `Background` is dropped.
+#[derive(Debug)]
+pub struct Background<T> {
+    latest: Arc<Mutex<T>>,
+}
+
+impl<T: Clone + PartialEq + Send + 'static> Background<T> {
+    /// Hold `initial` until the worker thread has something better.
+    ///
+    /// The worker thread first calls `start`, which connects to whatever is polled and returns the
+    /// function to poll it with, or `None` if there is nothing to poll. That function is then
+    /// called right away, and once every `interval` after that. Since all of this happens on the
+    /// worker thread, a source that is slow to respond never holds up the caller.
+    ///
+    /// Whenever the value changes, `notify` is called from the worker thread.
+    pub fn spawn<P: FnMut() -> T>(
+        initial: T,
+        start: impl FnOnce() -> Option<P> + Send + 'static,
+        interval: Duration,
+        notify: impl Fn() + Send + 'static,
+    ) -> Self {
+        let latest = Arc::new(Mutex::new(initial));
+        let shared = Arc::clone(&latest);
+        std::thread::spawn(move || {
+            let Some(mut poll) = start() else {
+                return;
+            };
+            loop {
+                // Poll without holding the lock, since this is what may take a while.
+                let value = poll();
+                let mut latest = shared.lock().unwrap();
+                if *latest != value {
+                    *latest = value;
+                    drop(latest);
+                    notify();
+                }
+
+                std::thread::sleep(interval);
+                if Arc::strong_count(&shared) == 1 {
+                    // Nobody is reading the values anymore.
+                    break;
+                }
+            }
+        });
+        Self { latest }
+    }
+
+    fn latest(&self) -> T {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+/// Poll the battery that `connect` finds every `interval` on a worker thread.
+///
+/// Until the first poll, the state of charge is unknown.
+pub fn battery<B: BatterySource>(
+    connect: impl FnOnce() -> Option<B> + Send + 'static,
+    interval: Duration,
+    notify: impl Fn() + Send + 'static,
+) -> Background<f32> {
+    let start = move || {
+        let mut battery = connect()?;
+        Some(move || {
+            battery.refresh();
+            battery.state_of_charge()
+        })
+    };
+    Background::spawn(f32::NAN, start, interval, notify)
+}
+
+/// Ask the music source that `connect` finds for its playback state every `interval` on a worker
+/// thread.
+///
+/// Until the first poll, the playback state is unknown.
+pub fn music<M: MusicSource>(
+    connect: impl FnOnce() -> Option<M> + Send + 'static,
+    interval: Duration,
+    notify: impl Fn() + Send + 'static,
+) -> Background<Option<mpd::State>> {
+    let start = move || {
+        let mut music = connect()?;
+        Some(move || music.playback_state())
+    };
+    Background::spawn(None, start, interval, notify)
+}
+
+impl BatterySource for Background<f32> {
+    fn refresh(&mut self) {
+        // The worker thread takes care of this.
+    }
+
+    fn state_of_charge(&self) -> f32 {
+        self.latest()
+    }
+}
+
+impl MusicSource for Background<Option<mpd::State>> {
+    fn playback_state(&mut self) -> Option<mpd::State> {
+        self.latest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::fake::{FakeBattery, Script};
+
+    #[test]
+    fn notify_on_change() {
+        let fake = FakeBattery {
+            charge: Script::new(vec![50.0]),
+        };
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let notify = move || {
+            let _ = sender.send(());
+        };
+        // The battery is only found once the test says so.
+        let (found, wait) = std::sync::mpsc::channel();
+        let connect = move || {
+            wait.recv().ok()?;
+            Some(fake)
+        };
+        // Only the first poll happens during the test.
+        let interval = Duration::from_secs(60 * 60);
+        let battery = battery(connect, interval, notify);
+
+        // The charge is unknown until the worker has polled the battery, and the worker reports
+        // when it has.
+        assert!(battery.state_of_charge().is_nan());
+        found.send(()).unwrap();
+        receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the worker notifies about the first value");
+        assert_eq!(battery.state_of_charge(), 50.0);
+    }
+}
@@ -0,0 +1,315 @@
+//! Data sources that replay scripted values, for tests and demos.
+
+use std::time::Duration;
+
+use super::{BatterySource, FrequencySource, MusicSource, SensorSource, Sources, SystemSource};
+
+/// A sequence of values that is stepped through on every refresh, starting over at the end.
+///
+/// Before the first refresh, the first value is current.
+#[derive(Debug, Clone)]
+pub struct Script<T> {
+    values: Vec<T>,
+    index: Option<usize>,
+}
+
+impl<T> Script<T> {
+    /// Create a script from its values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn new(values: Vec<T>) -> Self {
+        assert!(!values.is_empty(), "a script must have at least one value");
+        Self {
+            values,
+            index: None,
+        }
+    }
+
+    fn advance(&mut self) {
+        self.index = Some(self.index.map_or(0, |i| (i + 1) % self.values.len()));
+    }
+
+    fn current(&self) -> &T {
+        &self.values[self.index.unwrap_or(0)]
+    }
+}
+
+pub struct FakeSystem {
+    /// The usage of each cpu, as percentages.
+    pub cpus: Script<Vec<f32>>,
+    /// The used memory, in bytes.
+    pub used_memory: Script<u64>,
+    /// The total memory, in bytes.
+    pub total_memory: u64,
+    /// The memory used for buffers and for the page cache, in bytes.
+    pub buffers_and_cache: Option<(u64, u64)>,
+    /// The used swap, in bytes.
+    pub used_swap: u64,
+    /// The total swap, in bytes.
+    pub total_swap: u64,
+    /// The load averages over the last 1, 5 and 15 minutes.
+    pub load_average: [f64; 3],
+    pub uptime: Duration,
+    /// The number of processes.
+    pub processes: Script<usize>,
+    /// The bytes received and transmitted so far, for each network interface.
+    pub networks: Vec<(String, Script<(u64, u64)>)>,
+    /// The used and total space in bytes, for each mount point.
+    pub disks: Vec<(String, Script<(u64, u64)>)>,
+    /// The bytes read and written so far, for each block device.
+    pub disk_io: Vec<(String, Script<(u64, u64)>)>,
+}
+
+impl SystemSource for FakeSystem {
+    fn refresh_cpu(&mut self) {
+        self.cpus.advance()
+    }
+
+    fn refresh_memory(&mut self) {
+        self.used_memory.advance()
+    }
+
+    fn refresh_processes(&mut self) {
+        self.processes.advance()
+    }
+
+    fn refresh_networks(&mut self) {
+        for (_, totals) in &mut self.networks {
+            totals.advance()
+        }
+    }
+
+    fn refresh_networks_list(&mut self) {
+        // The interfaces never change.
+        self.refresh_networks()
+    }
+
+    fn refresh_disks(&mut self) {
+        for (_, script) in self.disks.iter_mut().chain(&mut self.disk_io) {
+            script.advance()
+        }
+    }
+
+    fn refresh_disks_list(&mut self) {
+        // Neither do the disks.
+        self.refresh_disks()
+    }
+
+    fn cpu_usages(&self) -> Vec<f32> {
+        self.cpus.current().clone()
+    }
+
+    fn used_memory(&self) -> u64 {
+        *self.used_memory.current()
+    }
+
+    fn total_memory(&self) -> u64 {
+        self.total_memory
+    }
+
+    fn buffers_and_cache(&self) -> Option<(u64, u64)> {
+        self.buffers_and_cache
+    }
+
+    fn used_swap(&self) -> u64 {
+        self.used_swap
+    }
+
+    fn total_swap(&self) -> u64 {
+        self.total_swap
+    }
+
+    fn load_average(&self) -> [f64; 3] {
+        self.load_average
+    }
+
+    fn uptime(&self) -> Duration {
+        self.uptime
+    }
+
+    fn process_count(&self) -> usize {
+        *self.processes.current()
+    }
+
+    fn network_totals(&self, interface: &str) -> Option<(u64, u64)> {
+        self.networks
+            .iter()
+            .find(|(name, _)| name == interface)
+            .map(|(_, totals)| *totals.current())
+    }
+
+    fn disk_usage(&self, mount_point: &str) -> Option<(u64, u64)> {
+        self.disks
+            .iter()
+            .find(|(name, _)| name == mount_point)
+            .map(|(_, usage)| *usage.current())
+    }
+
+    fn disk_io_totals(&self, device: &str) -> Option<(u64, u64)> {
+        self.disk_io
+            .iter()
+            .find(|(name, _)| name == device)
+            .map(|(_, totals)| *totals.current())
+    }
+}
+
+pub struct FakeSensors {
+    /// The label and temperature in degrees Celsius of each sensor.
+    pub temperatures: Script<Vec<(String, f32)>>,
+}
+
+impl SensorSource for FakeSensors {
+    fn refresh(&mut self) {
+        self.temperatures.advance()
+    }
+
+    fn temperatures(&self) -> Vec<(String, f32)> {
+        self.temperatures.current().clone()
+    }
+}
+
+pub struct FakeFrequencies {
+    /// The frequency of each cpu, in MHz.
+    pub frequencies: Script<Vec<u64>>,
+    pub governor: Option<String>,
+}
+
+impl FrequencySource for FakeFrequencies {
+    fn refresh(&mut self) {
+        self.frequencies.advance()
+    }
+
+    fn frequencies(&self) -> Vec<u64> {
+        self.frequencies.current().clone()
+    }
+
+    fn governor(&self) -> Option<String> {
+        self.governor.clone()
+    }
+}
+
+pub struct FakeBattery {
+    /// The state of charge, as percentages.
+    pub charge: Script<f32>,
+}
+
+impl BatterySource for FakeBattery {
+    fn refresh(&mut self) {
+        self.charge.advance()
+    }
+
+    fn state_of_charge(&self) -> f32 {
+        *self.charge.current()
+    }
+}
+
+pub struct FakeMusic {
+    /// The playback states, where `None` stands for an unreachable server.
+    pub states: Script<Option<mpd::State>>,
+}
+
+impl MusicSource for FakeMusic {
+    fn playback_state(&mut self) -> Option<mpd::State> {
+        self.states.advance();
+        *self.states.current()
+    }
+}
+
+/// Sources for a demonstration: four busy cpus, a network interface called `eth0` with bursts of
+/// traffic, a filling disk at `/` on `sda`, a cpu that heats up and speeds up under load, a slowly
+/// draining battery, and music that is paused now and then.
+pub fn demo() -> Sources {
+    const GIB: u64 = 1 << 30;
+    let cpus: Vec<Vec<f32>> = (0..60)
+        .map(|i| {
+            let t = i as f32 / 60.0 * std::f32::consts::TAU;
+            (0..4)
+                .map(|cpu| 50.0 + 45.0 * (t * (cpu + 1) as f32).sin())
+                .collect()
+        })
+        .collect();
+    let used_memory = (0..40).map(|i| 6 * GIB + (i % 20) * GIB / 10).collect();
+    let processes = (0..30).map(|i| 310 + i % 7 * 3).collect();
+    let mut totals = (0, 0);
+    let network = (0..30)
+        .map(|i| {
+            // A download now and then, with a trickle of uploads.
+            let rx = if i % 10 < 4 {
+                600 * 1024 * (i % 10 + 1)
+            } else {
+                2 * 1024
+            };
+            totals = (totals.0 + rx, totals.1 + 12 * 1024 + i * 512);
+            totals
+        })
+        .collect();
+    let temperatures = cpus
+        .iter()
+        .map(|usages| {
+            let usage = usages.iter().sum::<f32>() / usages.len() as f32;
+            vec![
+                ("coretemp Package id 0".to_string(), 40.0 + usage * 0.5),
+                ("acpitz temp1".to_string(), 27.8),
+            ]
+        })
+        .collect();
+    let frequencies = cpus
+        .iter()
+        .map(|usages| {
+            usages
+                .iter()
+                .map(|usage| 800 + (usage * 36.0) as u64)
+                .collect()
+        })
+        .collect();
+    let disk = (0..50).map(|i| (200 * GIB + i * GIB, 500 * GIB)).collect();
+    let mut totals = (0, 0);
+    let disk_io = (0..20)
+        .map(|i| {
+            // Steady writes, with a big read at the start.
+            let read = if i < 5 { 40 << 20 } else { 64 << 10 };
+            totals = (totals.0 + read, totals.1 + (3 << 20));
+            totals
+        })
+        .collect();
+    let charge = (0..=100).rev().map(|v| v as f32).collect();
+    let states = [mpd::State::Play; 20]
+        .into_iter()
+        .chain([mpd::State::Pause; 6])
+        .chain([mpd::State::Stop; 2])
+        .map(Some)
+        .collect();
+
+    Sources {
+        sys: Box::new(FakeSystem {
+            cpus: Script::new(cpus),
+            used_memory: Script::new(used_memory),
+            total_memory: 16 * GIB,
+            buffers_and_cache: Some((312 << 20, 4 * GIB + (100 << 20))),
+            used_swap: 300 << 20,
+            total_swap: 8 * GIB,
+            load_average: [2.13, 1.87, 1.52],
+            // Up since three days, four hours and twelve minutes.
+            uptime: Duration::from_secs(((3 * 24 + 4) * 60 + 12) * 60),
+            processes: Script::new(processes),
+            networks: vec![("eth0".to_string(), Script::new(network))],
+            disks: vec![("/".to_string(), Script::new(disk))],
+            disk_io: vec![("sda".to_string(), Script::new(disk_io))],
+        }),
+        sensors: Box::new(FakeSensors {
+            temperatures: Script::new(temperatures),
+        }),
+        frequency: Box::new(FakeFrequencies {
+            frequencies: Script::new(frequencies),
+            governor: Some("schedutil".to_string()),
+        }),
+        battery: Some(Box::new(FakeBattery {
+            charge: Script::new(charge),
+        })),
+        music: Some(Box::new(FakeMusic {
+            states: Script::new(states),
+        })),
+    }
+}
@@ -0,0 +1,232 @@
+//! Data sources that read from sysfs, where the kernel shows its devices, usually at `/sys`.
+
+use std::path::{Path, PathBuf};
+
+use super::{FrequencySource, SensorSource};
+
+/// The temperature sensors that the kernel shows in `class/hwmon` in sysfs.
+#[derive(Debug, Clone)]
+pub struct Hwmon {
+    dir: PathBuf,
+    temperatures: Vec<(String, f32)>,
+}
+
+impl Hwmon {
+    /// Read the sensors from the sysfs tree at `sysfs_root`.
+    ///
+    /// Nothing is read until the first refresh.
+    pub fn new(sysfs_root: &Path) -> Self {
+        Self {
+            dir: sysfs_root.join("class/hwmon"),
+            temperatures: Vec::new(),
+        }
+    }
+
+    /// Whether the hwmon tree is there at all.
+    pub fn exists(&self) -> bool {
+        self.dir.is_dir()
+    }
+}
+
+impl SensorSource for Hwmon {
+    fn refresh(&mut self) {
+        self.temperatures = read_temperatures(&self.dir).unwrap_or_default();
+    }
+
+    fn temperatures(&self) -> Vec<(String, f32)> {
+        self.temperatures.clone()
+    }
+}
+
+/// The frequencies and the governor of the cpus, which the kernel shows in `devices/system/cpu`
+/// in sysfs.
+#[derive(Debug, Clone)]
+pub struct CpuFrequencies {
+    dir: PathBuf,
+    frequencies: Vec<u64>,
+    governor: Option<String>,
+}
+
+impl CpuFrequencies {
+    /// Read the frequencies from the sysfs tree at `sysfs_root`.
+    ///
+    /// Nothing is read until the first refresh.
+    pub fn new(sysfs_root: &Path) -> Self {
+        Self {
+            dir: sysfs_root.join("devices/system/cpu"),
+            frequencies: Vec::new(),
+            governor: None,
+        }
+    }
+
+    /// Whether the kernel shows the frequencies at all, which is not the case in many virtual
+    /// machines.
+    pub fn exists(&self) -> bool {
+        self.dir.join("cpu0/cpufreq").is_dir()
+    }
+}
+
+impl FrequencySource for CpuFrequencies {
+    fn refresh(&mut self) {
+        let cpus = sorted_entries(&self.dir).unwrap_or_default();
+        let cpufreqs: Vec<PathBuf> = cpus
+            .iter()
+            .filter(|path| {
+                // Only the directories of the cpus themselves, such as `cpu0`.
+                let name = path.file_name().and_then(|name| name.to_str());
+                name.and_then(|name| name.strip_prefix("cpu"))
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .map(|path| path.join("cpufreq"))
+            .collect();
+        self.frequencies = cpufreqs
+            .iter()
+            .filter_map(|cpufreq| read_trimmed(&cpufreq.join("scaling_cur_freq")))
+            .filter_map(|khz| khz.parse::<u64>().ok())
+            .map(|khz| khz / 1000)
+            .collect();
+        self.governor = cpufreqs
+            .first()
+            .and_then(|cpufreq| read_trimmed(&cpufreq.join("scaling_governor")));
+    }
+
+    fn frequencies(&self) -> Vec<u64> {
+        self.frequencies.clone()
+    }
+
+    fn governor(&self) -> Option<String> {
+        self.governor.clone()
+    }
+}
+
+/// Read every temperature in the hwmon tree at `dir`.
+///
+/// The sensors are labeled in the same way as sysinfo does, with the name of the device followed
+/// by the label of the sensor, as in `coretemp Package id 0`.
+fn read_temperatures(dir: &Path) -> std::io::Result<Vec<(String, f32)>> {
+    let mut temperatures = Vec::new();
+    for device in sorted_entries(dir)? {
+        let name = read_trimmed(&device.join("name")).unwrap_or_default();
+        for path in sorted_entries(&device)? {
+            let Some(sensor) = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str()?.strip_suffix("_input"))
+                .filter(|sensor| sensor.starts_with("temp"))
+            else {
+                continue;
+            };
+            // Sensors that cannot be read right now are left out.
+            let Some(millidegrees) =
+                read_trimmed(&path).and_then(|value| value.parse::<f32>().ok())
+            else {
+                continue;
+            };
+            let label = read_trimmed(&device.join(format!("{sensor}_label")))
+                .unwrap_or_else(|| sensor.to_string());
+            temperatures.push((format!("{name} {label}"), millidegrees / 1000.0));
+        }
+    }
+    Ok(temperatures)
+}
+
+/// The paths in `dir`, in order.
+fn sorted_entries(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Read a sysfs attribute, without the trailing newline.
+fn read_trimmed(path: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    Some(contents.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sysfs tree in a fresh temporary directory, which is removed again when it is dropped.
+    struct FakeSysfs(PathBuf);
+
+    impl FakeSysfs {
+        /// Build the tree with the `files` and their contents.
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let root = std::env::temp_dir().join(format!("tid-{name}-{}", std::process::id()));
+            // Removed first, should an earlier run have left it behind.
+            let _ = std::fs::remove_dir_all(&root);
+            for (path, contents) in files {
+                let path = root.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, contents).unwrap();
+            }
+            Self(root)
+        }
+    }
+
+    impl Drop for FakeSysfs {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn hwmon_temperatures() {
+        // A labeled and an unlabeled sensor, and a fan that is not one.
+        let root = FakeSysfs::new(
+            "hwmon",
+            &[
+                ("class/hwmon/hwmon0/name", "coretemp\n"),
+                ("class/hwmon/hwmon0/temp1_input", "54000\n"),
+                ("class/hwmon/hwmon0/temp1_label", "Package id 0\n"),
+                ("class/hwmon/hwmon0/temp2_input", "51500\n"),
+                ("class/hwmon/hwmon1/name", "acpitz\n"),
+                ("class/hwmon/hwmon1/temp1_input", "27800\n"),
+                ("class/hwmon/hwmon1/fan1_input", "2400\n"),
+            ],
+        );
+
+        let mut sensors = Hwmon::new(&root.0);
+        assert!(sensors.exists());
+        sensors.refresh();
+        assert_eq!(
+            sensors.temperatures(),
+            [
+                ("coretemp Package id 0".to_string(), 54.0),
+                ("coretemp temp2".to_string(), 51.5),
+                ("acpitz temp1".to_string(), 27.8),
+            ]
+        );
+    }
+
+    #[test]
+    fn cpu_frequencies() {
+        // Two cpus, and a directory for all cpus that is not one of them.
+        let root = FakeSysfs::new(
+            "cpufreq",
+            &[
+                (
+                    "devices/system/cpu/cpu0/cpufreq/scaling_cur_freq",
+                    "2400000\n",
+                ),
+                (
+                    "devices/system/cpu/cpu0/cpufreq/scaling_governor",
+                    "schedutil\n",
+                ),
+                (
+                    "devices/system/cpu/cpu1/cpufreq/scaling_cur_freq",
+                    "1200000\n",
+                ),
+                ("devices/system/cpu/cpufreq/boost", "1\n"),
+            ],
+        );
+
+        let mut cpufreq = CpuFrequencies::new(&root.0);
+        assert!(cpufreq.exists());
+        cpufreq.refresh();
+        assert_eq!(cpufreq.frequencies(), [2400, 1200]);
+        assert_eq!(cpufreq.governor().as_deref(), Some("schedutil"));
+    }
+}
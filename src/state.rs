@@ -1,42 +1,48 @@
 use std::collections::VecDeque;
-use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-use battery::Battery;
-use chrono::{Datelike, Timelike};
-use pixels::Pixels;
-use sysinfo::{CpuExt, System, SystemExt};
-
-use crate::config::{Pixel, PIXEL_SIZE};
+use crate::config::Pixel;
+use crate::element::{Alignment, DateTime, Element};
 use crate::font::Font;
+use crate::framebuffer::Framebuffer;
+use crate::schedule::{Schedule, Scheduled};
+use crate::source::{SourceKind, Sources};
 
-const BATTERY_FULL_PERCENTAGE: f32 = 98.0;
+#[cfg(test)]
+mod tests;
 
+/// A rectangle of pixels, drawn by an element.
 #[derive(Debug, Clone)]
-struct Block {
+pub struct Block {
     height: usize,
     pixels: Vec<Pixel>,
 }
 
 impl Block {
-    fn width(&self) -> usize {
-        self.pixels.len() / self.height
+    /// Create a block from its `pixels`, stored row by row.
+    pub fn new(height: usize, pixels: Vec<Pixel>) -> Self {
+        Self { height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.len().checked_div(self.height).unwrap_or(0)
     }
 
-    fn rows(&self) -> std::slice::ChunksExact<'_, Pixel> {
-        self.pixels.chunks_exact(self.width())
+    /// The rows of the block, from top to bottom. A block without width has none.
+    pub fn rows(&self) -> std::slice::ChunksExact<'_, Pixel> {
+        let width = self.width();
+        let pixels = if width == 0 { &[] } else { &self.pixels[..] };
+        pixels.chunks_exact(width.max(1))
     }
 
-    fn draw_onto_pixels(self, pixels: &mut Pixels, start_x: usize) {
-        let size = pixels.texture().size();
+    fn draw_onto(self, framebuffer: &mut dyn Framebuffer, start_x: usize) {
         for (y, row) in self.rows().enumerate() {
-            let idx = (y * size.width as usize + start_x) * PIXEL_SIZE;
-            let row_bytes = row.flatten();
-            pixels.frame_mut()[idx..idx + row_bytes.len()].copy_from_slice(row_bytes);
+            framebuffer.put_row(start_x, y, row);
         }
     }
 }
 
-trait Draw {
+pub trait Draw {
     fn draw(&self, state: &State) -> Block;
 }
 
@@ -73,10 +79,10 @@ impl Draw for String {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct History<T>(VecDeque<T>);
+pub struct History<T>(VecDeque<T>);
 
 impl<T: Default + Clone> History<T> {
-    pub(crate) fn new(len: usize) -> Self {
+    pub fn new(len: usize) -> Self {
         Self(vec![Default::default(); len].into())
     }
 }
@@ -88,303 +94,243 @@ impl<T: Default + Clone> Default for History<T> {
 }
 
 impl<T> History<T> {
-    fn push(&mut self, value: T) {
+    pub fn push(&mut self, value: T) {
         let len = self.0.len();
         self.0.push_front(value);
         self.0.truncate(len);
     }
 
-    fn iter(&self) -> std::collections::vec_deque::Iter<'_, T> {
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, T> {
         self.0.iter()
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.0.len()
     }
-}
-
-type DateTime = chrono::DateTime<chrono::Local>;
 
-fn playback_state_symbol(state: mpd::State) -> &'static str {
-    match state {
-        mpd::State::Stop => "#",
-        mpd::State::Play => ">",
-        mpd::State::Pause => "\"",
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
-}
-
-#[derive(Debug, Clone)]
-pub enum Element {
-    Padding(usize),
-    Space,
-    Label(String),
-    Date(DateTime),
-    Time(DateTime),
-    Mem(f32),
-    Cpu(f32),
-    Battery(f32),
-    CpuGraph(History<f32>),
-    PlaybackState(mpd::State),
-}
 
-#[derive(Debug)]
-pub enum ElementParseError {
-    BadInteger(std::num::ParseIntError),
-    UnknownElementName(String),
-    UnknownArgumentedElementName(String),
-}
-
-impl std::fmt::Display for ElementParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ElementParseError::BadInteger(e) => e.fmt(f),
-            ElementParseError::UnknownElementName(weird) => {
-                write!(f, "unknown element name '{weird}'")
-            }
-            ElementParseError::UnknownArgumentedElementName(weird) => {
-                write!(f, "unknown argumented element name '{weird}'")
-            }
-        }
+    /// Create a history of the same length from the values in this one.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> History<U> {
+        History(self.0.iter().map(f).collect())
     }
 }
 
-impl std::error::Error for ElementParseError {}
-
-impl From<std::num::ParseIntError> for ElementParseError {
-    fn from(value: std::num::ParseIntError) -> Self {
-        Self::BadInteger(value)
+impl History<f32> {
+    /// Render the percentages as a line of block characters, newest first, in which each
+    /// character shows the highest of a run of `per_char` percentages.
+    pub fn sparkline(&self, per_char: usize) -> String {
+        let values: Vec<f32> = self.iter().copied().collect();
+        values
+            .chunks(per_char.max(1))
+            // NaN's are ignored by max, unless there is nothing else.
+            .map(|run| sparkline_char(run.iter().fold(f32::NAN, |a, &b| a.max(b))))
+            .collect()
     }
 }
 
-impl FromStr for Element {
-    type Err = ElementParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Some elements take a user-specifiable argument. We take care of these first.
-        if s.contains('(') && s.ends_with(')') {
-            let (name, argument) = s.split_once('(').unwrap();
-            let argument = argument.trim_end_matches(')');
-            let element = match name {
-                "padding" => Self::Padding(argument.parse::<usize>()?),
-                "label" => Self::Label(argument.to_string()),
-                "cpugraph" => Self::CpuGraph(History::new(argument.parse::<usize>()?)),
-                weird => Err(ElementParseError::UnknownArgumentedElementName(
-                    weird.to_string(),
-                ))?,
-            };
-            return Ok(element);
-        }
-
-        let element = match s {
-            "space" => Self::Space,
-            "date" => Self::Date(Default::default()),
-            "time" => Self::Time(Default::default()),
-            "battery" => Self::Battery(Default::default()),
-            "mem" => Self::Mem(Default::default()),
-            "cpu" => Self::Cpu(Default::default()),
-            "playbackstate" => Self::PlaybackState(Default::default()),
-            weird => Err(ElementParseError::UnknownElementName(weird.to_string()))?,
-        };
-        Ok(element)
-    }
+/// The block character that shows a percentage, from '▁' for nothing to '█' for all of it.
+pub fn sparkline_char(percentage: f32) -> char {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let level = (percentage / 100.0 * BARS.len() as f32) as usize;
+    BARS[level.min(BARS.len() - 1)]
 }
 
-impl Element {
-    fn width_with_font(&self, font: &Font) -> usize {
-        match self {
-            Element::Padding(width) => *width,
-            Element::Space => font.determine_width("  "),
-            Element::Label(s) => font.determine_width(s),
-            Element::Date(_) => font.determine_width("0000-00-00"),
-            Element::Time(_) => font.determine_width("00:00:00"),
-            Element::Mem(_) => font.determine_width("000%"),
-            Element::Cpu(_) => font.determine_width("000%"),
-            Element::Battery(_) => font.determine_width("000%"),
-            Element::CpuGraph(hist) => hist.len(),
-            Element::PlaybackState(_) => [mpd::State::Stop, mpd::State::Play, mpd::State::Pause]
-                .map(|state| font.determine_width(playback_state_symbol(state)))
-                .into_iter()
-                .max()
-                .unwrap(),
-        }
-    }
+/// How often the network interfaces and mounted disks are listed again.
+const LIST_INTERVAL: Duration = Duration::from_secs(60);
 
-    fn alignment(&self) -> Alignment {
-        match self {
-            Element::Padding(_)
-            | Element::Space
-            | Element::Label(_)
-            | Element::Mem(_)
-            | Element::Cpu(_)
-            | Element::Battery(_)
-            | Element::CpuGraph(_)
-            | Self::PlaybackState(_) => Alignment::Right,
-            Element::Date(_) | Element::Time(_) => Alignment::Left,
-        }
+/// Whether a list that was last made at `listed` should be made again at `now`, in which case
+/// `listed` is set to `now`.
+fn relist(listed: &mut Option<Instant>, now: Instant) -> bool {
+    if listed.is_some_and(|listed| now.saturating_duration_since(listed) < LIST_INTERVAL) {
+        return false;
     }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum Alignment {
-    Left,
-    Right,
+    *listed = Some(now);
+    true
 }
 
 pub struct State {
     pub font: Font,
-    sys: System,
-    battery: Option<Battery>,
-    music: Option<mpd::Client>,
+    sources: Sources,
     pub foreground: Pixel,
     pub background: Pixel,
-    elements: Vec<Element>,
+    /// The color of graphs, which is the foreground color unless it is set otherwise.
+    pub graph_color: Pixel,
+    elements: Vec<Box<dyn Element>>,
+    /// The name of each element in the elements specification, if it came from one.
+    names: Vec<Option<&'static str>>,
+    schedule: Schedule,
+    /// When the network interfaces were last listed, if ever.
+    networks_listed: Option<Instant>,
+    /// When the mounted disks were last listed, if ever.
+    disks_listed: Option<Instant>,
 }
 
 impl State {
     pub fn new(
         font: Font,
-        sys: System,
-        battery: Option<Battery>,
-        music: Option<mpd::Client>,
+        sources: Sources,
         foreground: Pixel,
         background: Pixel,
-        elements: Vec<Element>,
+        elements: Vec<Scheduled>,
     ) -> Self {
+        let schedule = Schedule::new(&elements);
+        let names = elements.iter().map(|scheduled| scheduled.name).collect();
+        let elements = elements
+            .into_iter()
+            .map(|scheduled| scheduled.element)
+            .collect();
         Self {
             font,
-            sys,
-            music,
-            battery,
+            sources,
             foreground,
             background,
+            graph_color: foreground,
             elements,
+            names,
+            schedule,
+            networks_listed: None,
+            disks_listed: None,
         }
     }
 
+    pub fn elements(&self) -> &[Box<dyn Element>] {
+        &self.elements
+    }
+
+    /// The name of each element in the elements specification, if it came from one.
+    pub fn names(&self) -> &[Option<&'static str>] {
+        &self.names
+    }
+
     pub fn window_size(&self) -> (u32, u32) {
-        let width: usize = self
-            .elements
-            .iter()
-            .map(|e| e.width_with_font(&self.font))
-            .sum();
+        let width: usize = self.elements.iter().map(|e| e.width(&self.font)).sum();
         let height = self.font.height();
         (width as u32, height as u32)
     }
 
+    /// Update all elements, whether they are due or not.
     pub fn update(&mut self) {
+        let all = (0..self.elements.len()).collect();
+        self.update_elements(all, Instant::now(), &chrono::Local::now());
+    }
+
+    /// Update all elements, and return the size of the window they take up then.
+    ///
+    /// Some elements only know their size after they are first updated, such as the bars of the
+    /// cpus before the number of cpus is known, so this is the size to start out with.
+    pub fn update_and_size(&mut self) -> (u32, u32) {
+        self.update();
+        self.window_size()
+    }
+
+    /// Update the elements that are due at `now`, and return whether there were any.
+    ///
+    /// The wall clock should read `wall` at the same moment, since aligned elements are scheduled
+    /// from both.
+    pub fn update_due(&mut self, now: Instant, wall: &DateTime) -> bool {
+        let due = (0..self.elements.len())
+            .filter(|&index| self.schedule.is_due(index, now))
+            .collect();
+        self.update_elements(due, now, wall)
+    }
+
+    /// Update the elements that read from `source`, such as when a background source has a new
+    /// value. Their schedule is left as is.
+    pub fn update_source(&mut self, source: SourceKind) {
+        let now = Instant::now();
+        for element in self.elements.iter_mut() {
+            if element.source() == Some(source) {
+                element.refresh(&mut self.sources, now);
+            }
+        }
+    }
+
+    /// The moment at which the next element is due for an update, if any element ever is.
+    pub fn next_update(&self) -> Option<Instant> {
+        self.schedule.next_deadline(Instant::now())
+    }
+
+    /// Update the elements at the indices in `due`, and return whether there were any.
+    fn update_elements(&mut self, due: Vec<usize>, now: Instant, wall: &DateTime) -> bool {
+        if due.is_empty() {
+            return false;
+        }
+
         // We refresh these once. This is good practice anyways, but refreshing multiple
         // times in quick succession may return NaN's on MacOS, apparently.
         // Thanks, Maya for noticing this!
-        self.sys.refresh_cpu();
-        self.sys.refresh_memory();
-
-        for element in self.elements.iter_mut() {
-            match element {
-                Element::Date(dt) | Element::Time(dt) => *dt = chrono::Local::now(),
-                Element::Mem(avl) => {
-                    let used = self.sys.used_memory() as f32;
-                    let available = self.sys.total_memory() as f32;
-                    *avl = used / available * 100.0;
-                }
-                Element::Cpu(avg) => {
-                    // FIXME: Sometimes on (at least) macOS, this returns NaN. This would crash the
-                    // program, so we have a NaN check when drawing the element.
-                    let cpus = self.sys.cpus();
-                    *avg = cpus.iter().map(|cpu| cpu.cpu_usage()).sum::<f32>() / cpus.len() as f32;
-                }
-                Element::Battery(full) => {
-                    if let Some(bat) = &mut self.battery {
-                        let _ = bat.refresh();
-                        *full = bat
-                            .state_of_charge()
-                            .get::<battery::units::ratio::percent>();
-                        // If the battery is basically full, just set it to 100%.
-                        if *full > BATTERY_FULL_PERCENTAGE {
-                            *full = 100.0
-                        }
-                    }
-                }
-                Element::CpuGraph(hist) => {
-                    let cpus = self.sys.cpus();
-                    let avg =
-                        cpus.iter().map(|cpu| cpu.cpu_usage()).sum::<f32>() / cpus.len() as f32;
-                    hist.push(avg);
-                }
-                Element::PlaybackState(state) => {
-                    // If we have access to mpd, and we get Some(Status) when we ask it, change the
-                    // state to that status' state.
-                    if let Some(status) = self.music.as_mut().and_then(|music| music.status().ok())
-                    {
-                        *state = status.state
-                    }
-                }
-                Element::Label(_) | Element::Padding(_) | Element::Space => {}
+        let needs = |source| {
+            due.iter()
+                .any(|&index| self.elements[index].source() == Some(source))
+        };
+        // The load and uptime are read anew every time, so they need no refresh.
+        if needs(SourceKind::Cpu) {
+            self.sources.sys.refresh_cpu();
+        }
+        if needs(SourceKind::Memory) {
+            self.sources.sys.refresh_memory();
+        }
+        if needs(SourceKind::Processes) {
+            self.sources.sys.refresh_processes();
+        }
+        // Interfaces and mounted disks come and go, but listing them is slow, so we only look for
+        // them again every so often.
+        if needs(SourceKind::Network) {
+            if relist(&mut self.networks_listed, now) {
+                self.sources.sys.refresh_networks_list();
+            } else {
+                self.sources.sys.refresh_networks();
             }
         }
+        if needs(SourceKind::Disk) {
+            if relist(&mut self.disks_listed, now) {
+                self.sources.sys.refresh_disks_list();
+            } else {
+                self.sources.sys.refresh_disks();
+            }
+        }
+        if needs(SourceKind::Sensors) {
+            self.sources.sensors.refresh();
+        }
+        if needs(SourceKind::Frequency) {
+            self.sources.frequency.refresh();
+        }
+
+        for index in due {
+            self.elements[index].update(&mut self.sources, now);
+            self.schedule.updated(index, now, wall);
+        }
+        true
     }
 
-    pub fn draw(&self, pixels: &mut Pixels) {
+    /// Draw the elements onto a `framebuffer` with the dimensions given by [`State::window_size`].
+    pub fn draw(&self, framebuffer: &mut dyn Framebuffer) {
         let mut x = 0;
         for element in &self.elements {
-            let block = match element {
-                Element::Padding(width) => {
-                    x += width;
-                    continue;
-                }
-                Element::Space => {
-                    x += self.font.determine_width("  ");
-                    continue;
-                }
-                Element::Label(s) => s.draw(self),
-                Element::Date(dt) => {
-                    format!("{:04}-{:02}-{:02}", dt.year(), dt.month(), dt.day()).draw(self)
-                }
-                Element::Time(dt) => {
-                    format!("{:02}:{:02}:{:02}", dt.hour(), dt.minute(), dt.second()).draw(self)
-                }
-                Element::Mem(val) | Element::Cpu(val) | Element::Battery(val) => {
-                    if val.is_nan() {
-                        "---%".draw(self)
-                    } else {
-                        format!("{val:>3.0}%").draw(self)
-                    }
-                }
-                Element::CpuGraph(hist) => {
-                    let height = self.window_size().1 as usize;
-                    let width = hist.len();
-                    let mut pixels = vec![self.background; height * width];
-
-                    for (x, usage) in hist.iter().enumerate() {
-                        let blank = height - ((usage / 100.0) * height as f32) as usize;
-                        for y in 0..height {
-                            let px = if y < blank {
-                                self.background
-                            } else {
-                                self.foreground
-                            };
-                            let idx = y * width + x;
-                            pixels[idx] = px;
-                        }
-                    }
-
-                    Block { height, pixels }
-                }
-                Element::PlaybackState(state) => playback_state_symbol(*state).draw(self),
+            let width = element.width(&self.font);
+            let Some(block) = element.draw(self) else {
+                // Elements that draw nothing just take up space.
+                x += width;
+                continue;
             };
 
             // We want to align some elements like cpu and memory percentages to the right, since
-            // their least significant digits change frequently and often displays a '1'.
+            // their least significant digits change frequently and often displays a '1'. An element
+            // that draws wider than it said it would just pushes the rest aside.
             let block_width = block.width();
-            let overshoot = element.width_with_font(&self.font) - block_width;
+            let overshoot = width.saturating_sub(block_width);
 
             match element.alignment() {
                 Alignment::Left => {
-                    block.draw_onto_pixels(pixels, x);
+                    block.draw_onto(framebuffer, x);
                     x += overshoot;
                 }
                 Alignment::Right => {
                     x += overshoot;
-                    block.draw_onto_pixels(pixels, x);
+                    block.draw_onto(framebuffer, x);
                 }
             }
 
@@ -0,0 +1,735 @@
+//! Tests for drawing and updating elements.
+//!
+//! The drawing tests draw elements with fixed data onto a [`Canvas`] and compare the result with a
+//! reference image in `tests/golden`. After an intended change in rendering, run the tests with
+//! `TID_BLESS=1` to write new reference images, and check them before committing.
+//!
+//! The update tests run elements against the scripted sources in [`crate::source::fake`].
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::TimeZone;
+use sysinfo::{System, SystemExt};
+
+use super::*;
+use crate::config::PIXEL_SIZE;
+use crate::element::*;
+use crate::font::testing::{psf2_font, uf2_font};
+use crate::framebuffer::Canvas;
+use crate::render::write_png;
+use crate::schedule::Scheduled;
+use crate::source::fake::{
+    FakeBattery, FakeFrequencies, FakeMusic, FakeSensors, FakeSystem, Script,
+};
+use crate::source::Sources;
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const FOREGROUND: Pixel = [0x00, 0x00, 0x00, 0xff];
+const BACKGROUND: Pixel = [0xff, 0xff, 0xff, 0xff];
+
+fn fonts() -> [(&'static str, Font); 2] {
+    [("uf2", uf2_font()), ("psf2", psf2_font())]
+}
+
+fn scheduled(elements: Vec<Box<dyn Element>>) -> Vec<Scheduled> {
+    elements.into_iter().map(Scheduled::from).collect()
+}
+
+fn state(font: Font, elements: Vec<Box<dyn Element>>) -> State {
+    let sources = Sources {
+        sys: Box::new(System::new()),
+        sensors: Box::new(System::new()),
+        frequency: Box::new(System::new()),
+        battery: None,
+        music: None,
+    };
+    State::new(font, sources, FOREGROUND, BACKGROUND, scheduled(elements))
+}
+
+fn draw(state: &State) -> Canvas {
+    let (width, height) = state.window_size();
+    let mut canvas = Canvas::new(width as usize, height as usize, state.background);
+    state.draw(&mut canvas);
+    canvas
+}
+
+fn datetime() -> DateTime {
+    chrono::Local
+        .with_ymd_and_hms(2023, 11, 14, 9, 5, 1)
+        .single()
+        .expect("the date is unambiguous")
+}
+
+/// Compare the canvas with the reference image called `name`.
+///
+/// If `TID_BLESS` is set, the reference image is (re)written instead.
+fn assert_golden(name: &str, canvas: &Canvas) {
+    let path = PathBuf::from_iter([GOLDEN_DIR, &format!("{name}.png")]);
+    if std::env::var_os("TID_BLESS").is_some() {
+        write_png(canvas, &path).expect("the reference image can be written");
+        return;
+    }
+
+    let file = std::fs::File::open(&path).unwrap_or_else(|err| {
+        panic!("could not open {path:?} ({err}), run with TID_BLESS=1 to create it")
+    });
+    let mut reader = png::Decoder::new(file)
+        .read_info()
+        .expect("the reference image is a valid png");
+    let mut expected = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut expected)
+        .expect("the reference image can be decoded");
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    let expected_size = (info.width as usize, info.height as usize);
+
+    if canvas.size() != expected_size || canvas.frame() != &expected[..info.buffer_size()] {
+        let actual = std::env::temp_dir().join(format!("tid-{name}.png"));
+        write_png(canvas, &actual).expect("the actual image can be written");
+        let differing = canvas
+            .frame()
+            .chunks_exact(PIXEL_SIZE)
+            .zip(expected.chunks_exact(PIXEL_SIZE))
+            .filter(|(a, b)| a != b)
+            .count();
+        panic!(
+            "{name} differs from {path:?}: size {:?} instead of {expected_size:?}, with \
+            {differing} differing pixels. The actual image was written to {actual:?}.",
+            canvas.size()
+        );
+    }
+}
+
+#[test]
+fn draw_str() {
+    for (font_name, font) in fonts() {
+        let state = state(font, Vec::new());
+        let block = "tid says: Hello, 2023!".draw(&state);
+        let mut canvas = Canvas::new(block.width(), block.height, state.background);
+        block.draw_onto(&mut canvas, 0);
+        assert_golden(&format!("{font_name}-str"), &canvas);
+    }
+}
+
+#[test]
+fn draw_str_unknown_glyphs_are_skipped() {
+    for (_, font) in fonts() {
+        let state = state(font, Vec::new());
+        let known = "ab".draw(&state);
+        let with_unknown = "a\u{1f980}b".draw(&state);
+        assert_eq!(known.pixels, with_unknown.pixels);
+    }
+}
+
+#[test]
+fn alignment() {
+    for (font_name, font) in fonts() {
+        let mut date = Date::default();
+        date.now = datetime();
+        let mut time = Time::default();
+        time.now = datetime();
+        let elements: Vec<Box<dyn Element>> = vec![
+            Box::new(Padding(3)),
+            Box::new(date),
+            Box::new(Space),
+            Box::new(time),
+            Box::new(Space),
+            Box::new(Label("cpu".to_string())),
+            Box::new(Cpu {
+                usage: 7.0,
+                ..Default::default()
+            }),
+            Box::new(Space),
+            Box::new(Label("mem".to_string())),
+            Box::new(Mem {
+                used: 16,
+                total: 16,
+                ..Default::default()
+            }),
+            Box::new(Space),
+            Box::new(Label("bat".to_string())),
+            Box::new(Battery(f32::NAN)),
+            Box::new(Space),
+            Box::new(PlaybackState(mpd::State::Pause)),
+            Box::new(Padding(3)),
+        ];
+        let state = state(font, elements);
+        assert_golden(&format!("{font_name}-alignment"), &draw(&state));
+    }
+}
+
+#[test]
+fn cpugraph() {
+    for (font_name, font) in fonts() {
+        let mut hist = History::new(60);
+        for i in 0..50 {
+            // A sawtooth, with some values that are out of the ordinary thrown in.
+            let usage = match i {
+                10 => 100.0,
+                20 => f32::NAN,
+                _ => (i % 17) as f32 * 6.25,
+            };
+            hist.push(usage);
+        }
+        let elements: Vec<Box<dyn Element>> = vec![
+            Box::new(Label("cpu".to_string())),
+            Box::new(CpuGraph {
+                history: hist,
+                ..Default::default()
+            }),
+            Box::new(Label("|".to_string())),
+        ];
+        let state = state(font, elements);
+        assert_golden(&format!("{font_name}-cpugraph"), &draw(&state));
+    }
+}
+
+#[test]
+fn graph_styles() {
+    const GRAPH_COLOR: Pixel = [0xcc, 0x33, 0xaa, 0xff];
+    for (font_name, font) in fonts() {
+        let mut history = History::new(40);
+        for i in 0..36 {
+            // A sawtooth with a gap, which breaks up a line.
+            let usage = match i {
+                20 => f32::NAN,
+                _ => (i % 12) as f32 * 9.0,
+            };
+            history.push(usage);
+        }
+        let mut elements: Vec<Box<dyn Element>> = vec![Box::new(Label("cpu".to_string()))];
+        for style in [
+            GraphStyle::Bars,
+            GraphStyle::Line,
+            GraphStyle::Dots,
+            GraphStyle::Mirror,
+            GraphStyle::Dither,
+        ] {
+            elements.push(Box::new(CpuGraph {
+                history: history.clone(),
+                style,
+            }));
+            elements.push(Box::new(Label("|".to_string())));
+        }
+        let mut state = state(font, elements);
+        state.graph_color = GRAPH_COLOR;
+        assert_golden(&format!("{font_name}-graphstyles"), &draw(&state));
+    }
+}
+
+#[test]
+fn netgraph() {
+    for (font_name, font) in fonts() {
+        let mut graph = NetGraph::new("eth0", 60);
+        for i in 0..50 {
+            // A steady download with a burst in the middle, and an upload that jumps up and down,
+            // and stops now and then.
+            let rx = if (20..30).contains(&i) {
+                8000.0
+            } else {
+                2000.0
+            };
+            let tx = [1000.0, 5000.0, 3000.0, 7000.0, 2000.0, 0.0][i % 6];
+            graph.rx.push(rx);
+            graph.tx.push(tx);
+        }
+        let elements: Vec<Box<dyn Element>> = vec![
+            Box::new(Label("net".to_string())),
+            Box::new(graph),
+            Box::new(Label("|".to_string())),
+        ];
+        let state = state(font, elements);
+        assert_golden(&format!("{font_name}-netgraph"), &draw(&state));
+    }
+
+    let registry = Registry::default();
+    assert!(registry.parse("netgraph(eth0, 60)").is_ok());
+    assert!(registry.parse("netgraph(eth0, 0)").is_err());
+}
+
+#[test]
+fn per_cpu_graphs() {
+    for (font_name, font) in fonts() {
+        let mut heat = CpuHeat(History::new(40));
+        for i in 0..30 {
+            // One cpu is pegged, while the others take turns.
+            let usages = (0..4)
+                .map(|cpu| match cpu {
+                    0 => 100.0,
+                    cpu => ((i + cpu * 7) % 10) as f32 * 11.0,
+                })
+                .collect();
+            heat.0.push(usages);
+        }
+        let elements: Vec<Box<dyn Element>> = vec![
+            Box::new(Label("cpu".to_string())),
+            Box::new(CpuCores(vec![100.0, 12.5, 50.0, f32::NAN])),
+            Box::new(Space),
+            Box::new(heat),
+            Box::new(Label("|".to_string())),
+        ];
+        let state = state(font, elements);
+        assert_golden(&format!("{font_name}-percpu"), &draw(&state));
+    }
+}
+
+fn fake_state(elements: Vec<Scheduled>) -> State {
+    let sys = FakeSystem {
+        cpus: Script::new(vec![vec![10.0, 30.0], vec![100.0, 50.0]]),
+        used_memory: Script::new(vec![4, 8]),
+        total_memory: 16,
+        buffers_and_cache: None,
+        used_swap: 0,
+        total_swap: 0,
+        load_average: [0.42, 12.345, 123.4],
+        uptime: Duration::from_secs(((3 * 24 + 4) * 60 + 12) * 60 + 5),
+        processes: Script::new(vec![312, 1045]),
+        networks: vec![(
+            "eth0".to_string(),
+            Script::new(vec![(1000, 100), (3000, 150)]),
+        )],
+        disks: vec![("/".to_string(), Script::new(vec![(3, 4), (1, 4)]))],
+        disk_io: vec![("sda".to_string(), Script::new(vec![(4096, 0), (8192, 512)]))],
+    };
+    let sensors = FakeSensors {
+        temperatures: Script::new(vec![vec![
+            ("coretemp Package id 0".to_string(), 54.0),
+            ("coretemp Core 0".to_string(), 51.0),
+        ]]),
+    };
+    let frequency = FakeFrequencies {
+        frequencies: Script::new(vec![vec![800, 3600], vec![2400, 2500]]),
+        governor: Some("powersave".to_string()),
+    };
+    let battery = FakeBattery {
+        charge: Script::new(vec![50.0, 99.0]),
+    };
+    let music = FakeMusic {
+        states: Script::new(vec![Some(mpd::State::Play), None, Some(mpd::State::Pause)]),
+    };
+    let sources = Sources {
+        sys: Box::new(sys),
+        sensors: Box::new(sensors),
+        frequency: Box::new(frequency),
+        battery: Some(Box::new(battery)),
+        music: Some(Box::new(music)),
+    };
+    State::new(psf2_font(), sources, FOREGROUND, BACKGROUND, elements)
+}
+
+fn texts(state: &State) -> Vec<String> {
+    state
+        .elements()
+        .iter()
+        .map(|element| element.text().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn update_from_sources() {
+    let elements: Vec<Box<dyn Element>> = vec![
+        Box::new(Cpu::default()),
+        Box::new(Mem::default()),
+        Box::new(Battery(Default::default())),
+        Box::new(PlaybackState(Default::default())),
+    ];
+    let mut state = fake_state(scheduled(elements));
+
+    state.update();
+    assert_eq!(texts(&state), [" 20%", " 25%", " 50%", ">"]);
+
+    // A nearly full battery is shown as full.
+    state.update();
+    assert_eq!(texts(&state), [" 75%", " 50%", "100%", ">"]);
+
+    // When the music source cannot be reached, the last known state is kept.
+    state.update();
+    assert_eq!(texts(&state), [" 20%", " 25%", " 50%", "\""]);
+}
+
+#[test]
+fn update_background_source() {
+    let registry = Registry::default();
+    let elements = ["graph(battery, 4)"]
+        .map(|spec| registry.parse(spec).expect("the spec is valid"))
+        .into();
+    let mut state = fake_state(elements);
+
+    // New values from a background source are taken in right away, but the graph only moves
+    // along with its schedule.
+    state.update();
+    state.update_source(SourceKind::Battery);
+    state.update_source(SourceKind::Battery);
+    state.update();
+
+    let mut expected = Graph::parse(Some("battery, 4")).expect("the argument is valid");
+    for charge in [50.0, 100.0] {
+        expected.history.push(charge);
+    }
+    let drawn = state.elements()[0].draw(&state).expect("a graph is drawn");
+    let expected = expected.draw(&state).expect("a graph is drawn");
+    assert_eq!(drawn.pixels, expected.pixels);
+}
+
+#[test]
+fn update_cpugraph_history() {
+    let mut state = fake_state(scheduled(vec![Box::new(CpuGraph::new(4))]));
+    for _ in 0..3 {
+        state.update();
+    }
+
+    let mut expected = History::new(4);
+    for usage in [20.0, 75.0, 20.0] {
+        expected.push(usage);
+    }
+    let drawn = state.elements()[0].draw(&state).expect("a graph is drawn");
+    let expected = CpuGraph {
+        history: expected,
+        ..Default::default()
+    };
+    let expected = expected.draw(&state).expect("a graph is drawn");
+    assert_eq!(drawn.pixels, expected.pixels);
+}
+
+#[test]
+fn update_due_elements() {
+    let registry = Registry::default();
+    let elements = ["cpu@1s", "battery@3s", "label(x@y)"]
+        .map(|spec| registry.parse(spec).expect("the spec is valid"))
+        .into();
+    let mut state = fake_state(elements);
+    let (start, wall) = (Instant::now(), chrono::Local::now());
+    let at = |secs| start + Duration::from_secs(secs);
+
+    // Everything is due right away, after which the label never is again.
+    assert!(state.update_due(at(0), &wall));
+    assert_eq!(texts(&state), [" 20%", " 50%", "x@y"]);
+    assert_eq!(state.next_update(), Some(at(1)));
+
+    assert!(!state.update_due(at(0), &wall));
+    assert!(state.update_due(at(1), &wall));
+    assert_eq!(texts(&state), [" 75%", " 50%", "x@y"]);
+
+    // Both are due, even though we are late for the cpu.
+    assert!(state.update_due(at(3), &wall));
+    assert_eq!(texts(&state), [" 20%", "100%", "x@y"]);
+    assert_eq!(state.next_update(), Some(at(4)));
+}
+
+#[test]
+fn align_to_the_given_wall_clock() {
+    let elements = ["time"]
+        .map(|spec| Registry::default().parse(spec).expect("the spec is valid"))
+        .into();
+    let mut state = fake_state(elements);
+    let start = Instant::now();
+    // However long the update takes, the next second is 100ms after the wall clock was read.
+    let wall = chrono::Local
+        .with_ymd_and_hms(2023, 11, 14, 9, 5, 1)
+        .single()
+        .expect("the date is unambiguous")
+        + chrono::Duration::milliseconds(900);
+    assert!(state.update_due(start, &wall));
+    let deadline = state.next_update().expect("the time is updated again");
+    assert!(deadline > start + Duration::from_millis(100));
+    assert!(deadline < start + Duration::from_millis(200));
+}
+
+#[test]
+fn relist_now_and_then() {
+    let start = Instant::now();
+    let mut listed = None;
+    assert!(relist(&mut listed, start));
+    assert!(!relist(&mut listed, start + Duration::from_secs(59)));
+    assert!(relist(&mut listed, start + LIST_INTERVAL));
+    assert_eq!(listed, Some(start + LIST_INTERVAL));
+}
+
+#[test]
+fn network_rates() {
+    let mut net = Net::new("eth0");
+    assert_eq!(net.text().unwrap(), "----/----");
+    for ((rx, tx), text) in [
+        ((0.0, 999.0), "  0B/999B"),
+        ((1000.0, 1536.0), "1.0K/1.5K"),
+        ((52_000.0, 12_000_000.0), " 51K/ 11M"),
+        ((3e9, 5e13), "2.8G/ 45T"),
+    ] {
+        net.rates = Some((rx, tx));
+        assert_eq!(net.text().unwrap(), text);
+    }
+
+    // The rates are only known from the second update on.
+    let mut state = fake_state(scheduled(vec![Box::new(Net::new("eth0"))]));
+    let (start, wall) = (Instant::now(), chrono::Local::now());
+    state.update_due(start, &wall);
+    assert_eq!(texts(&state), ["----/----"]);
+    state.update_due(start + Duration::from_secs(1), &wall);
+    assert_eq!(texts(&state), ["2.0K/ 50B"]);
+}
+
+#[test]
+fn disk_elements() {
+    let registry = Registry::default();
+    let elements = ["disk(/)", "disk(/mnt)", "diskio(/dev/sda)", "diskio(sdb)"]
+        .map(|spec| registry.parse(spec).expect("the spec is valid"))
+        .into();
+    let mut state = fake_state(elements);
+    let (start, wall) = (Instant::now(), chrono::Local::now());
+
+    state.update_due(start, &wall);
+    assert_eq!(texts(&state), [" 75%", "---%", "----/----", "----/----"]);
+    state.update_due(start + Duration::from_secs(2), &wall);
+    assert_eq!(texts(&state), [" 25%", "---%", "2.0K/256B", "----/----"]);
+}
+
+#[test]
+fn temperatures() {
+    let registry = Registry::default();
+    let elements = ["temp(package)", "temp(coretemp Core 0, F)", "temp(nvme)"]
+        .map(|spec| registry.parse(spec).expect("the spec is valid"))
+        .into();
+    let mut state = fake_state(elements);
+    state.update();
+    assert_eq!(texts(&state), [" 54C", "124F", "---C"]);
+    assert!(registry.parse("temp(package, K)").is_err());
+}
+
+#[test]
+fn cpu_frequencies() {
+    let registry = Registry::default();
+    let elements = ["cpufreq", "cpufreq(max)", "cpufreq(max, gov)"]
+        .map(|spec| registry.parse(spec).expect("the spec is valid"))
+        .into();
+    let mut state = fake_state(elements);
+    state.update();
+    assert_eq!(texts(&state), ["2.2G", "3.6G", "3.6G powersave   "]);
+    state.update();
+    assert_eq!(texts(&state), ["2.5G", "2.5G", "2.5G powersave   "]);
+    assert!(registry.parse("cpufreq(min)").is_err());
+
+    let mut cpufreq = CpuFreq {
+        show_governor: true,
+        frequency: Some(800),
+        governor: Some("turbo".to_string()),
+        ..Default::default()
+    };
+    // Any governor shows, in a fixed number of characters.
+    assert_eq!(cpufreq.text().unwrap(), "800M turbo       ");
+    cpufreq.governor = Some("intel_cpufreq_passive".to_string());
+    assert_eq!(cpufreq.text().unwrap(), "800M intel_cpufre");
+    let text_width = state.font.determine_width(&cpufreq.text().unwrap());
+    assert!(text_width <= cpufreq.width(&state.font));
+    cpufreq.frequency = None;
+    cpufreq.governor = None;
+    assert_eq!(cpufreq.text().unwrap(), "-.-G ?           ");
+}
+
+#[test]
+fn load_uptime_and_procs() {
+    let registry = Registry::default();
+    let elements = [
+        "load",
+        "uptime",
+        "uptime(%H:%M:%S)",
+        "uptime(up %d%%)",
+        "procs",
+    ]
+    .map(|spec| registry.parse(spec).expect("the spec is valid"))
+    .into();
+    let mut state = fake_state(elements);
+    state.update();
+    assert_eq!(
+        texts(&state),
+        ["0.42 12.3  123", "3d 04:12", "76:12:05", "up 3%", "312"]
+    );
+    assert!(registry.parse("uptime(%Y)").is_err());
+
+    // The largest unit never outgrows its width, even after years.
+    let font = psf2_font();
+    for (format, expected) in [
+        ("%dd %H:%M", "999d 23:59"),
+        ("%H:%M", "23999:59"),
+        ("%M", "1439999"),
+    ] {
+        let mut uptime = Uptime::new(format).expect("the format is valid");
+        uptime.uptime = Duration::from_secs(5000 * 24 * 60 * 60);
+        assert_eq!(uptime.text().unwrap(), expected);
+        assert_eq!(uptime.width(&font), font.determine_width(expected));
+    }
+}
+
+/// An element that draws wider than it says it is.
+#[derive(Debug)]
+struct TooWide;
+
+impl Element for TooWide {
+    fn parse(_argument: Option<&str>) -> Result<Self, ElementParseError> {
+        Ok(Self)
+    }
+
+    fn width(&self, _font: &Font) -> usize {
+        1
+    }
+
+    fn text(&self) -> Option<String> {
+        Some("much too wide".to_string())
+    }
+}
+
+#[test]
+fn draw_too_wide() {
+    // The rest is pushed aside and cut off, but the bar is still drawn.
+    let elements: Vec<Box<dyn Element>> = vec![Box::new(TooWide), Box::new(Label("x".to_string()))];
+    let state = state(psf2_font(), elements);
+    let canvas = draw(&state);
+    assert_eq!(canvas.size(), (1 + 6, 10));
+}
+
+#[test]
+fn draw_nothing() {
+    // An uptime without a format would have nothing to show.
+    assert!(Registry::default().parse("uptime()").is_err());
+
+    // An element that turns out to draw nothing at all takes up no space.
+    let elements: Vec<Box<dyn Element>> = vec![
+        Box::new(Label(String::new())),
+        Box::new(Label("x".to_string())),
+    ];
+    let state = state(psf2_font(), elements);
+    let canvas = draw(&state);
+    assert_eq!(canvas.size(), (6, 10));
+}
+
+#[test]
+fn memory_views() {
+    const GIB: u64 = 1 << 30;
+    let registry = Registry::default();
+    let elements = [
+        "mem",
+        "mem(abs)",
+        "mem(avail)",
+        "mem(cache)",
+        "swap",
+        "swap(abs)",
+    ]
+    .map(|spec| registry.parse(spec).expect("the spec is valid"))
+    .into();
+    let mut state = fake_state(elements);
+    state.update();
+    assert_eq!(
+        texts(&state),
+        [
+            " 25%",
+            "  4B/ 16B",
+            " 12B",
+            "----/----",
+            "---%",
+            "  0B/  0B"
+        ]
+    );
+    assert!(registry.parse("swap(cache)").is_err());
+    assert!(registry.parse("mem(free)").is_err());
+
+    let mem = Mem {
+        view: MemoryView::Absolute,
+        used: 5 * GIB + GIB / 5,
+        total: 16 * GIB,
+        buffers_and_cache: None,
+    };
+    assert_eq!(mem.text().unwrap(), "5.2G/ 16G");
+    let mem = Mem {
+        view: MemoryView::Cache,
+        buffers_and_cache: Some((312 << 20, 4 * GIB + (100 << 20))),
+        ..mem
+    };
+    assert_eq!(mem.text().unwrap(), "312M/4.1G");
+}
+
+#[test]
+fn per_cpu_usage() {
+    let registry = Registry::default();
+    let elements = ["cpu", "cpu(0)", "cpu(1)", "cpu(2)", "cpucores"]
+        .map(|spec| registry.parse(spec).expect("the spec is valid"))
+        .into_iter()
+        .chain([
+            Scheduled::from(Box::new(CpuCores::new(3)) as Box<dyn Element>),
+            Scheduled::from(Box::new(CpuHeat(History::new(16))) as Box<dyn Element>),
+        ])
+        .collect();
+    let mut state = fake_state(elements);
+    // The number of cpus is not known until the first update.
+    assert_eq!(state.elements()[4].width(&state.font), 0);
+    assert!(state.elements()[4].draw(&state).is_none());
+    draw(&state);
+    state.update();
+    assert_eq!(
+        texts(&state),
+        [" 20%", " 10%", " 30%", "---%", "▁▃", "▁▃▁", "▃▁"]
+    );
+    state.update();
+    assert_eq!(
+        texts(&state),
+        [" 75%", "100%", " 50%", "---%", "█▅", "█▅▁", "█▁"]
+    );
+
+    // With more cpus than rows, a single busy cpu still shows up.
+    let height = state.font.height();
+    let mut usages = vec![0.0; height * 3];
+    usages[height + 1] = 100.0;
+    let mut heat = CpuHeat(History::new(1));
+    heat.0.push(usages);
+    let block = heat.draw(&state).expect("the heatmap draws");
+    let lit = block
+        .rows()
+        .filter(|row| row[0] == state.graph_color)
+        .count();
+    assert_eq!(lit, 1);
+}
+
+#[test]
+fn sparklines() {
+    let mut history = History::new(20);
+    for value in [50.0, 100.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 30.0] {
+        history.push(value);
+    }
+    // Each character shows the highest of its run, the last of which is short.
+    assert_eq!(history.sparkline(8), "▃█▁");
+    assert_eq!(history.sparkline(20), "█");
+    assert_eq!(history.sparkline(1).chars().count(), 20);
+}
+
+#[test]
+fn generic_graphs() {
+    let registry = Registry::default();
+    let elements = [
+        "graph(cpu, 16)",
+        "graph(cpu(1), 16)",
+        "graph(mem(abs), 8)",
+        "graph(temp(coretemp Core 0, F), 8, min=68, max=212)",
+        "graph(procs, 16, style=dots)",
+    ]
+    .map(|spec| registry.parse(spec).expect("the spec is valid"))
+    .into();
+    let mut state = fake_state(elements);
+    state.update();
+    state.update();
+    // Values without a range, like the number of processes, scale to the highest one.
+    assert_eq!(texts(&state), ["▇▁", "▅▁", "▅", "▄", "█▁"]);
+
+    for spec in [
+        "graph(cpu)",
+        "graph(cpu, 0)",
+        "graph(time, 8)",
+        "graph(cpu, 8, min=low)",
+        "graph(cpu, 8, avg)",
+        "graph(graph(cpu, 8), 8)",
+        "graph(cpu, 8, style=spline)",
+        "cpugraph(8, min=0)",
+        "cpugraph(0)",
+        "cpuheat(0)",
+    ] {
+        assert!(registry.parse(spec).is_err(), "{spec}");
+    }
+}
@@ -0,0 +1,226 @@
+use pixels::Pixels;
+
+use crate::framebuffer::Framebuffer;
+
+/// The surface that presents the framebuffer in the window.
+pub enum Surface {
+    /// Rendering through wgpu.
+    Gpu(Box<Pixels>),
+    /// Rendering on the cpu, for machines without a working wgpu adapter.
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    Software(Box<x11::SoftwareSurface>),
+}
+
+impl Surface {
+    /// Present the current contents of the framebuffer.
+    pub fn render(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Surface::Gpu(pixels) => pixels.render()?,
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            Surface::Software(surface) => surface.render()?,
+        }
+        Ok(())
+    }
+}
+
+impl Framebuffer for Surface {
+    fn size(&self) -> (usize, usize) {
+        match self {
+            Surface::Gpu(pixels) => Framebuffer::size(pixels.as_ref()),
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            Surface::Software(surface) => surface.size(),
+        }
+    }
+
+    fn frame(&self) -> &[u8] {
+        match self {
+            Surface::Gpu(pixels) => pixels.frame(),
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            Surface::Software(surface) => surface.frame(),
+        }
+    }
+
+    fn frame_mut(&mut self) -> &mut [u8] {
+        match self {
+            Surface::Gpu(pixels) => pixels.frame_mut(),
+            #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+            Surface::Software(surface) => surface.frame_mut(),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+pub mod x11 {
+    use std::os::raw::{c_char, c_uint};
+
+    use winit::platform::x11::WindowExtX11;
+    use winit::window::Window;
+    use x11_dl::xlib;
+
+    use crate::config::{Pixel, PIXEL_SIZE};
+    use crate::framebuffer::Framebuffer;
+
+    /// A surface that is presented with plain `XPutImage` calls, without involving the gpu.
+    pub struct SoftwareSurface {
+        xlib: xlib::Xlib,
+        display: *mut xlib::Display,
+        window: xlib::Window,
+        gc: xlib::GC,
+        visual: *mut xlib::Visual,
+        depth: c_uint,
+        width: usize,
+        height: usize,
+        scale_factor: usize,
+        frame: Vec<u8>,
+        /// The scaled-up frame in the byte layout of the window's visual.
+        image: Vec<u8>,
+    }
+
+    impl SoftwareSurface {
+        /// Set up a software surface of `width` by `height` logical pixels for `window`.
+        ///
+        /// Each logical pixel is presented as `scale_factor` by `scale_factor` pixels.
+        pub fn new(
+            window: &Window,
+            width: usize,
+            height: usize,
+            scale_factor: usize,
+            background: Pixel,
+        ) -> Result<Self, String> {
+            let (Some(display), Some(window)) = (window.xlib_display(), window.xlib_window())
+            else {
+                return Err("software rendering is only supported on X11".to_string());
+            };
+            let display = display as *mut xlib::Display;
+            let xlib = xlib::Xlib::open().map_err(|err| err.to_string())?;
+
+            // Safety: The display and window are valid for as long as the winit window lives, and
+            // the surface is dropped together with it.
+            let (gc, visual, depth) = unsafe {
+                let mut attributes = std::mem::zeroed::<xlib::XWindowAttributes>();
+                if (xlib.XGetWindowAttributes)(display, window, &mut attributes) == 0 {
+                    return Err("could not get the window attributes".to_string());
+                }
+                let gc = (xlib.XCreateGC)(display, window, 0, std::ptr::null_mut());
+                (gc, attributes.visual, attributes.depth as c_uint)
+            };
+            if depth != 24 && depth != 32 {
+                return Err(format!("unsupported window depth of {depth} bits"));
+            }
+
+            let image_len = width * height * scale_factor * scale_factor * PIXEL_SIZE;
+            Ok(Self {
+                xlib,
+                display,
+                window,
+                gc,
+                visual,
+                depth,
+                width,
+                height,
+                scale_factor,
+                frame: background.repeat(width * height),
+                image: vec![0; image_len],
+            })
+        }
+
+        /// Present the current frame in the window.
+        pub fn render(&mut self) -> Result<(), String> {
+            let scaled_width = self.width * self.scale_factor;
+            let scaled_height = self.height * self.scale_factor;
+            for (y, row) in self.frame.chunks_exact(self.width * PIXEL_SIZE).enumerate() {
+                let scaled_row: Vec<u8> = row
+                    .array_chunks::<PIXEL_SIZE>()
+                    .flat_map(|&px| {
+                        let px = bgra(px, self.depth == 32);
+                        std::iter::repeat_n(px, self.scale_factor)
+                    })
+                    .flatten()
+                    .collect();
+                for sy in 0..self.scale_factor {
+                    let idx = (y * self.scale_factor + sy) * scaled_row.len();
+                    self.image[idx..idx + scaled_row.len()].copy_from_slice(&scaled_row);
+                }
+            }
+
+            // Safety: The image only borrows our buffer. Its data pointer is reset before it is
+            // destroyed, such that Xlib does not try to free memory it does not own.
+            unsafe {
+                let image = (self.xlib.XCreateImage)(
+                    self.display,
+                    self.visual,
+                    self.depth,
+                    xlib::ZPixmap,
+                    0,
+                    self.image.as_mut_ptr() as *mut c_char,
+                    scaled_width as c_uint,
+                    scaled_height as c_uint,
+                    32,
+                    0,
+                );
+                if image.is_null() {
+                    return Err("could not create an XImage".to_string());
+                }
+                (*image).byte_order = xlib::LSBFirst;
+                let bits_per_pixel = (*image).bits_per_pixel;
+                if bits_per_pixel == 32 {
+                    (self.xlib.XPutImage)(
+                        self.display,
+                        self.window,
+                        self.gc,
+                        image,
+                        0,
+                        0,
+                        0,
+                        0,
+                        scaled_width as c_uint,
+                        scaled_height as c_uint,
+                    );
+                    (self.xlib.XFlush)(self.display);
+                }
+                (*image).data = std::ptr::null_mut();
+                (self.xlib.XDestroyImage)(image);
+                if bits_per_pixel != 32 {
+                    return Err(format!("unsupported image format of {bits_per_pixel} bpp"));
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Convert an rgba pixel to the little-endian argb layout that X expects.
+    ///
+    /// Windows with an alpha channel expect premultiplied colors.
+    fn bgra([r, g, b, a]: Pixel, with_alpha: bool) -> Pixel {
+        if with_alpha {
+            let premultiply = |v: u8| (v as u16 * a as u16 / u8::MAX as u16) as u8;
+            [premultiply(b), premultiply(g), premultiply(r), a]
+        } else {
+            [b, g, r, u8::MAX]
+        }
+    }
+
+    impl Framebuffer for SoftwareSurface {
+        fn size(&self) -> (usize, usize) {
+            (self.width, self.height)
+        }
+
+        fn frame(&self) -> &[u8] {
+            &self.frame
+        }
+
+        fn frame_mut(&mut self) -> &mut [u8] {
+            &mut self.frame
+        }
+    }
+
+    impl Drop for SoftwareSurface {
+        fn drop(&mut self) {
+            // Safety: The graphics context was created by us on this display.
+            unsafe {
+                (self.xlib.XFreeGC)(self.display, self.gc);
+            }
+        }
+    }
+}
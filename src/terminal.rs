@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::config::{Pixel, PIXEL_SIZE};
+use crate::framebuffer::Framebuffer;
+
+/// The largest number of colors we put in a sixel palette.
+const SIXEL_MAX_COLORS: usize = 256;
+
+/// The size of the base64-encoded chunks of a kitty image transmission.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// The id under which we transmit our image, such that each new frame replaces the previous one.
+const KITTY_IMAGE_ID: u32 = 1;
+
+/// Encode the framebuffer as a sixel image.
+///
+/// Every pixel is painted, with any transparency drawn over black. Where a terminal layers each
+/// new image over the previous one, leaving pixels out would let the previous frame show through.
+pub fn sixel(framebuffer: &dyn Framebuffer) -> String {
+    let (width, height) = framebuffer.size();
+    let over_black = |v: u8, a: u8| (v as u32 * a as u32 / u8::MAX as u32) as u8;
+    let pixels: Vec<Pixel> = framebuffer
+        .frame()
+        .array_chunks::<PIXEL_SIZE>()
+        .map(|&[r, g, b, a]| {
+            [
+                over_black(r, a),
+                over_black(g, a),
+                over_black(b, a),
+                u8::MAX,
+            ]
+        })
+        .collect();
+
+    // If there are too many distinct colors, we reduce them to 3-3-2 bit rgb, which has exactly
+    // as many colors as we allow.
+    let quantize = |[r, g, b, a]: Pixel| [r & 0xe0, g & 0xe0, b & 0xc0, a];
+    let mut exact = true;
+    let (mut palette, mut indices) = build_palette(pixels.iter().copied());
+    if palette.len() > SIXEL_MAX_COLORS {
+        exact = false;
+        (palette, indices) = build_palette(pixels.iter().map(|&px| quantize(px)));
+    }
+    let index_of = |px: Pixel| indices[&if exact { px } else { quantize(px) }];
+
+    // Start the image with an opaque background, and set the aspect ratio to 1:1.
+    let mut out = String::from("\x1bP0;0;0q");
+    write!(out, "\"1;1;{width};{height}").unwrap();
+    let percent = |v: u8| v as u32 * 100 / u8::MAX as u32;
+    for (i, &[r, g, b, _]) in palette.iter().enumerate() {
+        write!(out, "#{i};2;{};{};{}", percent(r), percent(g), percent(b)).unwrap();
+    }
+
+    // Each band of six rows is drawn once for every color that occurs in it.
+    for band in (0..height).step_by(6) {
+        let rows = band..usize::min(band + 6, height);
+        let mut colors: Vec<usize> = rows
+            .clone()
+            .flat_map(|y| &pixels[y * width..(y + 1) * width])
+            .map(|&px| index_of(px))
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (n, &color) in colors.iter().enumerate() {
+            if n > 0 {
+                // Return to the start of the band to draw the next color over it.
+                out.push('$');
+            }
+            write!(out, "#{color}").unwrap();
+            let sixels = (0..width).map(|x| {
+                let bits = rows
+                    .clone()
+                    .enumerate()
+                    .filter(|&(_, y)| index_of(pixels[y * width + x]) == color)
+                    .fold(0, |bits, (dy, _)| bits | 1 << dy);
+                char::from(0x3f + bits as u8)
+            });
+            push_run_length_encoded(&mut out, sixels);
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Collect the distinct colors in order of appearance, along with a map to their index.
+fn build_palette(colors: impl Iterator<Item = Pixel>) -> (Vec<Pixel>, HashMap<Pixel, usize>) {
+    let mut palette = Vec::new();
+    let mut indices = HashMap::new();
+    for px in colors {
+        indices.entry(px).or_insert_with(|| {
+            palette.push(px);
+            palette.len() - 1
+        });
+    }
+    (palette, indices)
+}
+
+/// Push the sixel characters, using the repeat introducer for runs of the same character.
+fn push_run_length_encoded(out: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |out: &mut String, (ch, count): (char, usize)| match count {
+        1..=3 => (0..count).for_each(|_| out.push(ch)),
+        _ => write!(out, "!{count}{ch}").unwrap(),
+    };
+    for ch in sixels {
+        match &mut run {
+            Some((prev, count)) if *prev == ch => *count += 1,
+            _ => {
+                if let Some(run) = run {
+                    flush(out, run);
+                }
+                run = Some((ch, 1));
+            }
+        }
+    }
+    if let Some(run) = run {
+        flush(out, run);
+    }
+}
+
+/// Encode the framebuffer as an image in the kitty graphics protocol.
+///
+/// The image is placed at the cursor, without moving it. Each image is transmitted with the same
+/// id, so it replaces the previous one.
+pub fn kitty(framebuffer: &dyn Framebuffer) -> String {
+    let (width, height) = framebuffer.size();
+    let data = base64(framebuffer.frame());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+
+    let mut out = String::new();
+    for (n, chunk) in chunks.iter().enumerate() {
+        let more = (n + 1 < chunks.len()) as u8;
+        // Base64 is plain ascii, so this cannot fail.
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if n == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=32,s={width},v={height},i={KITTY_IMAGE_ID},p=1,C=1,q=2,m={more};{chunk}\x1b\\"
+            )
+            .unwrap();
+        } else {
+            write!(out, "\x1b_Gm={more};{chunk}\x1b\\").unwrap();
+        }
+    }
+    out
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Canvas;
+
+    const RED: Pixel = [0xff, 0x00, 0x00, 0xff];
+    const WHITE: Pixel = [0xff, 0xff, 0xff, 0xff];
+
+    #[test]
+    fn base64_vectors() {
+        let cases = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(base64(input.as_bytes()), expected, "{input}");
+        }
+        assert_eq!(base64(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+
+    #[test]
+    fn kitty_chunks() {
+        let canvas = Canvas::new(1, 1, [0x00, 0x00, 0x00, 0xff]);
+        assert_eq!(
+            kitty(&canvas),
+            "\x1b_Ga=T,f=32,s=1,v=1,i=1,p=1,C=1,q=2,m=0;AAAA/w==\x1b\\"
+        );
+
+        // 32 by 32 pixels take 4096 bytes, which take more than one chunk in base64.
+        let canvas = Canvas::new(32, 32, RED);
+        let out = kitty(&canvas);
+        let commands: Vec<&str> = out.split_terminator("\x1b\\").collect();
+        let [first, second] = commands.as_slice() else {
+            panic!("expected two commands, got {commands:?}");
+        };
+        let first = first
+            .strip_prefix("\x1b_Ga=T,f=32,s=32,v=32,i=1,p=1,C=1,q=2,m=1;")
+            .expect("the first command transmits the image, with more to come");
+        let second = second
+            .strip_prefix("\x1b_Gm=0;")
+            .expect("the second command is the last");
+        assert_eq!(first.len(), KITTY_CHUNK_SIZE);
+        assert_eq!(second.len(), 4096_usize.div_ceil(3) * 4 - KITTY_CHUNK_SIZE);
+        assert_eq!(format!("{first}{second}"), base64(canvas.frame()));
+    }
+
+    #[test]
+    fn sixel_bands() {
+        // Seven rows take two bands, of six rows and of one.
+        let mut canvas = Canvas::new(4, 7, RED);
+        canvas.put_row(0, 0, &[WHITE]);
+        assert_eq!(
+            sixel(&canvas),
+            concat!(
+                "\x1bP0;0;0q\"1;1;4;7",
+                // The colors in order of appearance.
+                "#0;2;100;100;100#1;2;100;0;0",
+                // Each color in the first band, returning to its start in between.
+                "#0@???$#1}~~~-",
+                "#1!4@-",
+                "\x1b\\",
+            )
+        );
+
+        // Transparency is drawn over black.
+        let canvas = Canvas::new(1, 1, [0xff, 0xff, 0xff, 0x80]);
+        assert_eq!(
+            sixel(&canvas),
+            "\x1bP0;0;0q\"1;1;1;1#0;2;50;50;50#0@-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_quantization() {
+        let palette_len = |out: &str| out.matches(";2;").count();
+
+        let mut canvas = Canvas::new(SIXEL_MAX_COLORS + 1, 1, RED);
+        let row: Vec<Pixel> = (0..SIXEL_MAX_COLORS)
+            .map(|x| [x as u8, 0x00, 0x00, 0xff])
+            .collect();
+        canvas.put_row(0, 0, &row);
+        // The last pixel is still red, so the colors fit in the palette exactly.
+        assert_eq!(palette_len(&sixel(&canvas)), SIXEL_MAX_COLORS);
+
+        // With one color too many, the reds are reduced to their top three bits.
+        canvas.put_row(SIXEL_MAX_COLORS, 0, &[[0x00, 0x20, 0x00, 0xff]]);
+        let out = sixel(&canvas);
+        assert_eq!(palette_len(&out), 9);
+        assert!(out.contains("#1;2;12;0;0"), "{out}");
+        assert!(out.contains("#8;2;0;12;0"), "{out}");
+    }
+}
@@ -0,0 +1,262 @@
+//! Showing the bar in a window of its own.
+
+use std::time::Instant;
+
+use pixels::wgpu::BlendState;
+use pixels::{PixelsBuilder, SurfaceTexture};
+use winit::dpi::{LogicalPosition, PhysicalSize};
+use winit::event::Event;
+use winit::event_loop::EventLoop;
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+use winit::platform::x11::{WindowBuilderExtX11, XWindowType};
+use winit::window::{Window, WindowBuilder, WindowLevel};
+use winit_input_helper::WinitInputHelper;
+#[cfg(target_os = "macos")]
+use {
+    cocoa::appkit::{NSWindow, NSWindowCollectionBehavior},
+    objc::runtime::{objc_release, objc_retain, Object},
+    winit::platform::macos::WindowExtMacOS,
+};
+
+use crate::config::{Pixel, Renderer};
+use crate::framebuffer::Framebuffer;
+use crate::source::SourceKind;
+use crate::state::State;
+use crate::surface::{self, Surface};
+
+const WINDOW_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Determine scale factor.
+///
+/// In order to deal well with higher resolution displays, fractional scale factors (e.g., 1.67)
+/// are quantized to integers.
+///
+/// We can get the scale factor from the window, but in order to create the window, we must
+/// first know the scale factor. To circumvent that circular mess, we can create a dummy window,
+/// read its scale factor, and use that to eventually set up our actual window. The dummy window
+/// is dropped immediately after its created.
+/// Note that this method relies on the assumption that both times we create a winit::Window, the
+/// monitor it picks (and its scale factor) will be the same.
+///
+/// If the environment variable is not set, the scale factor is determined with the dummy window
+/// method.
+fn determine_scale_factor(event_loop: &EventLoop<SourceKind>) -> u32 {
+    const DEFAULT_SCALE_FACTOR: f64 = 1.0;
+    let wm_scale_factor = || {
+        let Ok(dummy) = Window::new(event_loop) else {
+            eprintln!(
+                "INFO:  Could not construct dummy window to measure scale factor, \
+                    assuming a factor of {DEFAULT_SCALE_FACTOR}"
+            );
+            return DEFAULT_SCALE_FACTOR;
+        };
+
+        dummy.scale_factor()
+    };
+
+    env_scale_factor().unwrap_or(wm_scale_factor().round() as u32)
+}
+
+/// Read the scale factor from the `TID_SCALE_FACTOR` environment variable, if it is set.
+pub(crate) fn env_scale_factor() -> Option<u32> {
+    std::env::var("TID_SCALE_FACTOR")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok().map(|f| u32::max(1, f.round() as u32)))
+}
+
+fn setup_window(
+    size: PhysicalSize<u32>,
+    position: LogicalPosition<u32>,
+    event_loop: &EventLoop<SourceKind>,
+) -> Window {
+    let builder = WindowBuilder::new()
+        .with_active(false)
+        .with_decorations(false)
+        .with_resizable(false)
+        .with_transparent(true)
+        .with_title(WINDOW_NAME)
+        .with_inner_size(size)
+        .with_max_inner_size(size)
+        .with_min_inner_size(size)
+        .with_position(position)
+        .with_window_level(WindowLevel::AlwaysOnTop);
+
+    // On Linux (and BSDs, which I have not been able to test), Wayland and X11 are supported. On
+    // these platforms, we can set a name. On X11 specifically, we want to set the window type to
+    // Dock, which means it is properly treated as an immovable bar.
+    //
+    // Thanks to Maya.
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    let builder = builder
+        .with_base_size(size)
+        .with_name(WINDOW_NAME, WINDOW_NAME)
+        .with_x11_window_type(vec![XWindowType::Dock]);
+
+    #[allow(unused_mut)]
+    let mut window = builder.build(event_loop).expect("could not build window");
+    #[cfg(target_os = "macos")]
+    make_window_sticky_on_mac(&mut window);
+    window
+}
+
+fn setup_gpu_surface(
+    width: u32,
+    height: u32,
+    background: Pixel,
+    window: &Window,
+) -> Result<Surface, pixels::Error> {
+    let window_size = window.inner_size();
+    let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, window);
+    let pixels = PixelsBuilder::new(width, height, surface_texture)
+        .clear_color({
+            let [r, g, b, a] = background.map(|v| v as f64 / u8::MAX as f64);
+            pixels::wgpu::Color { r, g, b, a }
+        })
+        .blend_state(BlendState::REPLACE) // TODO: Investigate rendering weirdness.
+        .build()?;
+    Ok(Surface::Gpu(Box::new(pixels)))
+}
+
+#[allow(unused_variables)]
+fn setup_software_surface(
+    width: u32,
+    height: u32,
+    scale_factor: u32,
+    background: Pixel,
+    window: &Window,
+) -> Result<Surface, String> {
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+    return surface::x11::SoftwareSurface::new(
+        window,
+        width as usize,
+        height as usize,
+        scale_factor as usize,
+        background,
+    )
+    .map(|surface| Surface::Software(Box::new(surface)));
+
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+    Err("software rendering is not supported on this platform".to_string())
+}
+
+/// Set up the surface that presents our pixels in the window, according to the `renderer`.
+///
+/// When the renderer is [`Renderer::Auto`], we first try to use the gpu. Some machines do not
+/// have a working wgpu adapter, so in that case we fall back to software rendering.
+fn setup_surface(
+    renderer: Renderer,
+    width: u32,
+    height: u32,
+    scale_factor: u32,
+    background: Pixel,
+    window: &Window,
+) -> Result<Surface, Box<dyn std::error::Error>> {
+    let surface = match renderer {
+        Renderer::Gpu => setup_gpu_surface(width, height, background, window)?,
+        Renderer::Software => {
+            setup_software_surface(width, height, scale_factor, background, window)?
+        }
+        Renderer::Auto => match setup_gpu_surface(width, height, background, window) {
+            Ok(surface) => surface,
+            Err(err) => {
+                eprintln!(
+                    "INFO:  Could not set up gpu rendering ({err}), using software rendering"
+                );
+                setup_software_surface(width, height, scale_factor, background, window)?
+            }
+        },
+    };
+    Ok(surface)
+}
+
+#[cfg(target_os = "macos")]
+fn make_window_sticky_on_mac(window: &mut Window) {
+    let mac_window = window as &dyn WindowExtMacOS;
+    let ns_window_id = mac_window.ns_window();
+    // Safety: ns_window_id points to a valid NSWindow Object and is non-null.
+    unsafe {
+        let ns_window: *mut Object = std::mem::transmute(ns_window_id);
+        objc_retain(ns_window);
+        ns_window.setCollectionBehavior_(
+            NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces,
+        );
+        objc_release(ns_window);
+    }
+}
+
+/// Open a window at `position` that shows the bar, drawn with the `renderer`, and keep it up to
+/// date until the window is closed.
+///
+/// Background sources wake up the `event_loop` with the kind of source that has something new.
+pub fn run(
+    mut state: State,
+    event_loop: EventLoop<SourceKind>,
+    position: LogicalPosition<u32>,
+    renderer: Renderer,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let scale_factor = determine_scale_factor(&event_loop);
+
+    let (width, height) = state.update_and_size();
+    let size = PhysicalSize::new(width * scale_factor, height * scale_factor);
+
+    let mut input = WinitInputHelper::new();
+    let window = setup_window(size, position, &event_loop);
+
+    let mut surface = setup_surface(
+        renderer,
+        width,
+        height,
+        scale_factor,
+        state.background,
+        &window,
+    )
+    .map_err(|err| format!("Failed to set up rendering: {err}"))?;
+
+    event_loop.run(move |event, _, control_flow| {
+        // Sleep until the next element is due.
+        match state.next_update() {
+            Some(deadline) => control_flow.set_wait_until(deadline),
+            None => control_flow.set_wait(),
+        }
+
+        match event {
+            Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
+                window.request_redraw()
+            }
+            Event::UserEvent(source) => {
+                state.update_source(source);
+                window.request_redraw()
+            }
+            Event::RedrawRequested(_) => {
+                // Clear the screen before drawing.
+                surface.clear(state.background);
+
+                // Update whatever is due, then draw.
+                state.update_due(Instant::now(), &chrono::Local::now());
+                state.draw(&mut surface);
+
+                // Try to render.
+                if let Err(err) = surface.render() {
+                    eprintln!("ERROR: {err}");
+                    control_flow.set_exit();
+                    return;
+                }
+            }
+            _ => (),
+        }
+
+        if input.update(&event) {
+            // Close events.
+            if input.close_requested() {
+                eprintln!("INFO:  Close requested. Bye :)");
+                control_flow.set_exit();
+                return;
+            }
+
+            // Resize the window.
+            if let Some(size) = input.window_resized() {
+                eprintln!("INFO:  Ignoring resize request {size:?}");
+            }
+        }
+    });
+}